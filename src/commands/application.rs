@@ -1,27 +1,337 @@
-use crate::commands::{self, Result};
+use crate::commands::{self, Command, Result};
 use crate::errors::*;
-use crate::input::KeyMap;
+use crate::input::{Key, KeyMap};
 use crate::models::application::modes::*;
 use crate::models::application::{Application, Mode};
+use crate::presenters;
 use crate::util;
+use scribe::buffer::Position;
 use scribe::Buffer;
+use smallvec::SmallVec;
 use std::mem;
+use std::path::Path;
+
+// Larger counts still work as line numbers, but
+// commands aren't repeated more than this many times.
+const MAX_REPETITIONS: usize = 10_000;
+
+// Entry point for keys typed by the user, which are recorded
+// so that changes and macros can be replayed later.
+pub(crate) fn handle_key(app: &mut Application, key: Key) -> Result {
+    if at_rest(app) {
+        if let Some(buffer) = app.workspace.current_buffer.as_ref() {
+            if let Some(id) = buffer.id {
+                app.changes.start(id, app.view.revision(buffer));
+            }
+        }
+    }
+    app.changes.record(key.clone());
+    app.macros.push_key(key.clone());
+    app.view.last_key = Some(key);
+
+    let result = handle_input(app);
+    finish_change(app);
+    if app.pending_keys.is_empty() {
+        app.macros.end_sequence();
+    }
+
+    result
+}
+
+// Runs a held key sequence once the user stops typing.
+pub(crate) fn handle_key_timeout(app: &mut Application) -> Result {
+    let result = flush_pending_keys(app);
+    finish_change(app);
+    app.macros.end_sequence();
+
+    result
+}
+
+fn finish_change(app: &mut Application) {
+    if app.changes.is_recording() && at_rest(app) {
+        let buffer = app.workspace.current_buffer.as_ref();
+        let revision = buffer.map_or(0, |buffer| app.view.revision(buffer));
+        app.changes
+            .finish(buffer.and_then(|buffer| buffer.id), revision);
+    }
+}
+
+// Changes start and end in normal mode, without a partial key sequence or count.
+fn at_rest(app: &Application) -> bool {
+    matches!(app.mode, Mode::Normal) && app.pending_keys.is_empty() && app.count.is_none()
+}
+
+pub fn repeat_last_change(app: &mut Application) -> Result {
+    let keys = app.changes.last_change().to_vec();
+    if keys.is_empty() {
+        bail!("No change to repeat");
+    }
+
+    // Replaying a change isn't a change of its own.
+    app.changes.cancel();
+
+    replay_keys(app, keys)
+}
+
+pub fn record_macro(app: &mut Application) -> Result {
+    if app.macros.recording_register().is_some() {
+        app.macros.stop();
+
+        return Ok(());
+    }
+
+    match app.view.last_key() {
+        Some(Key::Char(register)) => app.macros.start(*register),
+        _ => bail!("Macro registers are named using a character"),
+    }
+
+    Ok(())
+}
+
+pub fn play_macro(app: &mut Application) -> Result {
+    let register = match app.view.last_key() {
+        Some(Key::Char(register)) => *register,
+        _ => bail!("Macro registers are named using a character"),
+    };
+    let keys = app
+        .macros
+        .register(register)
+        .ok_or_else(|| format!("Macro register \"{}\" is empty", register))?
+        .to_vec();
+
+    play_keys(app, &register.to_string(), keys)
+}
+
+fn play_keys(app: &mut Application, name: &str, keys: Vec<Key>) -> Result {
+    if !app.macros.start_playing(name) {
+        bail!(format!("Macro \"{}\" can't play itself", name));
+    }
+    let result = replay_keys(app, keys);
+    app.macros.stop_playing(name);
+
+    result
+}
+
+// Feeds keys through the keymap as though they'd been typed,
+// restoring the key that triggered the replay afterwards.
+fn replay_keys(app: &mut Application, keys: Vec<Key>) -> Result {
+    let last_key = app.view.last_key.take();
+    let mut result = Ok(());
+    for key in keys {
+        app.view.last_key = Some(key);
+        result = handle_input(app);
+        if result.is_err() {
+            break;
+        }
+    }
+
+    // There's no timeout to complete a sequence left hanging.
+    if result.is_ok() && !app.pending_keys.is_empty() {
+        result = flush_pending_keys(app);
+    }
+    app.view.last_key = last_key;
+
+    result
+}
 
 pub fn handle_input(app: &mut Application) -> Result {
-    let commands = app.view.last_key().as_ref().and_then(|key| {
-        app.mode_str()
-            .and_then(|mode| app.preferences.borrow().keymap().commands_for(mode, key))
-    });
+    if app.pending_keys.is_empty() && push_count_digit(app) {
+        return Ok(());
+    }
+
+    let (key, mode) = match (app.view.last_key().clone(), app.mode_str()) {
+        (Some(key), Some(mode)) => (key, mode),
+        _ => return Ok(()),
+    };
+    let mut keys = mem::take(&mut app.pending_keys);
+    keys.push(key);
+
+    // Keys that start a longer binding are held until the sequence is
+    // complete or abandoned, even if they're also bound on their own.
+    if app.preferences.borrow().keymap().is_prefix(mode, &keys) {
+        app.pending_keys = keys;
+        return Ok(());
+    }
+
+    let binding = binding_for(app, &keys);
+    if binding.is_none() && keys.len() > 1 {
+        // The sequence was abandoned; run whatever the held keys
+        // are bound to, and then handle the latest key on its own.
+        let key = keys.pop();
+        app.pending_keys = keys;
+        flush_pending_keys(app)?;
+        app.view.last_key = key;
+
+        return handle_input(app);
+    }
+
+    let count = app.count.take();
+    if let Some(binding) = binding {
+        run_binding(app, binding, count)?;
+    }
+
+    Ok(())
+}
+
+// Runs the longest held prefix that's bound on its own, and
+// then replays the remaining keys one at a time.
+pub(crate) fn flush_pending_keys(app: &mut Application) -> Result {
+    let mut keys = mem::take(&mut app.pending_keys);
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let length = (1..=keys.len())
+        .rev()
+        .find(|&length| binding_for(app, &keys[..length]).is_some())
+        .unwrap_or(1);
+    let remaining = keys.split_off(length);
+    let count = app.count.take();
+
+    if let Some(binding) = binding_for(app, &keys) {
+        app.view.last_key = keys.last().cloned();
+        run_binding(app, binding, count)?;
+    }
+    for key in remaining {
+        app.view.last_key = Some(key);
+        handle_input(app)?;
+    }
+
+    flush_pending_keys(app)
+}
+
+enum Binding {
+    Commands(SmallVec<[Command; 4]>),
+    Macro(String),
+}
+
+fn binding_for(app: &Application, keys: &[Key]) -> Option<Binding> {
+    let mode = app.mode_str()?;
+    let preferences = app.preferences.borrow();
+    let keymap = preferences.keymap();
+
+    keymap
+        .sequence_commands_for(mode, keys)
+        .map(Binding::Commands)
+        .or_else(|| keymap.sequence_macro_for(mode, keys).map(Binding::Macro))
+}
+
+fn run_binding(app: &mut Application, binding: Binding, count: Option<usize>) -> Result {
+    let result = match binding {
+        Binding::Commands(coms) => run_with_count(app, &coms, count),
+        Binding::Macro(name) => run_macro(app, &name, count),
+    };
+    app.clipboard.finish_command();
+
+    result
+}
+
+fn run_macro(app: &mut Application, name: &str, count: Option<usize>) -> Result {
+    let keys = app.preferences.borrow().macro_keys(name)?;
+
+    repeat(app, count, |app| play_keys(app, name, keys.clone()))
+}
+
+// Runs a command up to count times, stopping once it has switched
+// modes, or when repeating it further wouldn't do anything.
+fn repeat<F>(app: &mut Application, count: Option<usize>, mut run: F) -> Result
+where
+    F: FnMut(&mut Application) -> Result,
+{
+    let mut edits = false;
+    for _ in 0..count.unwrap_or(1).min(MAX_REPETITIONS) {
+        let state = buffer_state(app, edits);
+        run(app)?;
+        let new_state = buffer_state(app, edits);
+
+        if !matches!(app.mode, Mode::Normal) || new_state == state {
+            break;
+        }
+        edits = edits || new_state.map(|new| new.revision) != state.map(|old| old.revision);
+    }
+
+    Ok(())
+}
+
+// Digits typed in normal mode build up a count for the next command,
+// though a leading zero is left to the keymap.
+fn push_count_digit(app: &mut Application) -> bool {
+    let digit = match (&app.mode, app.view.last_key()) {
+        (Mode::Normal, Some(Key::Char(c))) => c.to_digit(10),
+        _ => None,
+    };
+
+    match digit {
+        Some(0) if app.count.is_none() => false,
+        Some(digit) => {
+            let count = app.count.unwrap_or(0);
+            app.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+
+            true
+        }
+        None => false,
+    }
+}
+
+fn run_with_count(app: &mut Application, coms: &[Command], count: Option<usize>) -> Result {
+    let grouped = count.is_some() && matches!(app.mode, Mode::Normal);
+    if grouped {
+        app.hold_operation_group();
+    }
 
-    if let Some(coms) = commands {
+    let result = repeat(app, count, |app| {
         for com in coms {
             com(app)?;
         }
+
+        Ok(())
+    });
+
+    // Insert mode closes the group itself, so that
+    // the repeated commands and the insert undo together.
+    if grouped {
+        app.release_operation_group();
+    }
+    result?;
+
+    if let (Some(count), Mode::LineJump(mode)) = (count, &mut app.mode) {
+        mode.input = count.to_string();
+        commands::line_jump::accept_input(app)?;
     }
 
     Ok(())
 }
 
+// What a repeated command could change: the current buffer, its contents,
+// and where its cursor and view are. Edits that leave the contents as they
+// were still move the revision, so once a command has edited the buffer,
+// its contents are compared instead.
+#[derive(PartialEq)]
+struct BufferState {
+    id: Option<usize>,
+    revision: usize,
+    data: Option<String>,
+    cursor: Position,
+    scroll_offset: usize,
+}
+
+fn buffer_state(app: &mut Application, with_data: bool) -> Option<BufferState> {
+    let buffer = app.workspace.current_buffer.as_ref()?;
+    let (revision, data) = if with_data {
+        (0, Some(buffer.data()))
+    } else {
+        (app.view.revision(buffer), None)
+    };
+
+    Some(BufferState {
+        id: buffer.id,
+        revision,
+        data,
+        cursor: *buffer.cursor,
+        scroll_offset: app.view.scroll_offset(buffer).unwrap_or(0),
+    })
+}
+
 pub fn switch_to_normal_mode(app: &mut Application) -> Result {
     let _ = commands::buffer::end_command_group(app);
     app.mode = Mode::Normal;
@@ -81,6 +391,7 @@ pub fn switch_to_second_stage_jump_mode(app: &mut Application) -> Result {
 
 pub fn switch_to_line_jump_mode(app: &mut Application) -> Result {
     if app.workspace.current_buffer.is_some() {
+        app.line_jump_history.reset();
         app.mode = Mode::LineJump(LineJumpMode::new());
     } else {
         bail!(BUFFER_MISSING);
@@ -92,10 +403,39 @@ pub fn switch_to_line_jump_mode(app: &mut Application) -> Result {
 pub fn switch_to_open_mode(app: &mut Application) -> Result {
     let exclusions = app.preferences.borrow().open_mode_exclusions()?;
     let config = app.preferences.borrow().search_select_config();
+
+    // Open mode lists paths relative to the workspace, which is
+    // where recently opened files have to be for it to find them.
+    let recent = if app.preferences.borrow().open_mode_recency() {
+        app.recent_files
+            .entries()
+            .filter_map(|path| Path::new(path).strip_prefix(&app.workspace.path).ok())
+            .map(Path::to_path_buf)
+            .collect()
+    } else {
+        Vec::new()
+    };
     app.mode = Mode::Open(OpenMode::new(
         app.workspace.path.clone(),
         exclusions,
         app.event_channel.clone(),
+        recent,
+        config,
+    ));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_grep_mode(app: &mut Application) -> Result {
+    let exclusions = app.preferences.borrow().open_mode_exclusions()?;
+    let case = app.preferences.borrow().search_case();
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Grep(GrepMode::new(
+        app.workspace.path.clone(),
+        exclusions,
+        case,
+        app.event_channel.clone(),
         config,
     ));
     commands::search_select::search(app)?;
@@ -144,6 +484,7 @@ pub fn switch_to_theme_mode(app: &mut Application) -> Result {
 pub fn switch_to_select_mode(app: &mut Application) -> Result {
     if let Some(buffer) = app.workspace.current_buffer.as_ref() {
         app.mode = Mode::Select(SelectMode::new(*buffer.cursor.clone()));
+        app.cursors.anchor_selections();
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -161,9 +502,25 @@ pub fn switch_to_select_line_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_select_block_mode(app: &mut Application) -> Result {
+    if let Some(buffer) = app.workspace.current_buffer.as_ref() {
+        app.mode = Mode::SelectBlock(SelectBlockMode::new(*buffer.cursor.clone()));
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    Ok(())
+}
+
 pub fn switch_to_search_mode(app: &mut Application) -> Result {
     if app.workspace.current_buffer.is_some() {
-        app.mode = Mode::Search(SearchMode::new(app.search_query.clone()));
+        let case = app.preferences.borrow().search_case();
+        app.search_history.reset();
+        app.mode = Mode::Search(SearchMode::new(
+            app.search_query.clone(),
+            app.search_regex,
+            case,
+        ));
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -171,6 +528,24 @@ pub fn switch_to_search_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_replace_mode(app: &mut Application) -> Result {
+    match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Search(mode) => {
+            if mode.results.as_ref().map(|r| r.is_empty()).unwrap_or(true) {
+                app.mode = Mode::Search(mode);
+                bail!(NO_SEARCH_RESULTS);
+            }
+            app.mode = Mode::Replace(ReplaceMode::new(mode));
+        }
+        mode => {
+            app.mode = mode;
+            bail!("Can't replace search results outside of search mode");
+        }
+    }
+
+    Ok(())
+}
+
 pub fn switch_to_path_mode(app: &mut Application) -> Result {
     let path = app
         .workspace
@@ -183,11 +558,95 @@ pub fn switch_to_path_mode(app: &mut Application) -> Result {
             p.to_string_lossy().into_owned())
         .unwrap_or_else(||
             format!("{}/", app.workspace.path.to_string_lossy()));
+    app.path_history.reset();
     app.mode = Mode::Path(PathMode::new(path));
 
     Ok(())
 }
 
+pub fn switch_to_clipboard_history_mode(app: &mut Application) -> Result {
+    if app.workspace.current_buffer.is_none() {
+        bail!(BUFFER_MISSING);
+    }
+
+    let entries = app
+        .clipboard
+        .history()
+        .cloned()
+        .filter_map(ClipboardEntry::new)
+        .collect();
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::ClipboardHistory(ClipboardHistoryMode::new(entries, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_recent_files_mode(app: &mut Application) -> Result {
+    let paths = app.recent_files.entries().map(String::from).collect();
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::RecentFiles(RecentFilesMode::new(paths, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_buffer_list_mode(app: &mut Application) -> Result {
+    let start = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .and_then(|buf| buf.id)
+        .ok_or(BUFFER_MISSING)?;
+    app.track_buffer_use();
+
+    // Visit every buffer, returning to the current one.
+    let mut entries = Vec::new();
+    loop {
+        if let Some(buf) = app.workspace.current_buffer.as_ref() {
+            let path = app
+                .workspace
+                .current_buffer_path()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("[no path]"));
+
+            entries.push(BufferEntry {
+                id: buf.id.unwrap_or_default(),
+                path,
+                modified: buf.modified(),
+                status: presenters::git_status(&app.repository, &buf.path),
+            });
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer.as_ref().and_then(|buf| buf.id) == Some(start) {
+            break;
+        }
+    }
+
+    // Buffers that haven't been switched to yet are listed last, in workspace order.
+    let recent_buffers = &app.recent_buffers;
+    entries.sort_by_key(|entry| {
+        recent_buffers
+            .iter()
+            .position(|id| *id == entry.id)
+            .unwrap_or(recent_buffers.len())
+    });
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::BufferList(BufferListMode::new(entries, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn select_register(app: &mut Application) -> Result {
+    match app.view.last_key() {
+        Some(Key::Char(register)) => app.clipboard.select_register(*register),
+        _ => bail!("Registers are named using a character"),
+    }
+}
+
 pub fn switch_to_syntax_mode(app: &mut Application) -> Result {
     let _ = app
         .workspace
@@ -268,6 +727,10 @@ pub fn suspend(app: &mut Application) -> Result {
 }
 
 pub fn exit(app: &mut Application) -> Result {
+    // Failing to save the session shouldn't keep the editor open.
+    if app.preferences.borrow().session() {
+        let _ = commands::session::save(app);
+    }
     app.mode = Mode::Exit;
 
     Ok(())
@@ -275,10 +738,18 @@ pub fn exit(app: &mut Application) -> Result {
 
 #[cfg(test)]
 mod tests {
-    use crate::models::application::Mode;
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::modes::SearchSelectMode;
+    use crate::models::application::{ClipboardContent, History, Mode};
     use crate::models::Application;
+    use crate::util;
+    use scribe::buffer::Position;
     use scribe::Buffer;
-    use std::path::PathBuf;
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
 
     #[test]
     fn display_available_commands_creates_a_new_buffer() {
@@ -362,4 +833,369 @@ mod tests {
 
         assert!(super::switch_to_path_mode(&mut app).is_err());
     }
+
+    fn press_keys(app: &mut Application, keys: &str) {
+        for c in keys.chars() {
+            super::handle_key(app, Key::Char(c)).unwrap();
+        }
+    }
+
+    #[test]
+    fn handle_input_repeats_commands_using_a_count_prefix() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit editor");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        press_keys(&mut app, "3x");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "edit editor"
+        );
+        assert_eq!(app.count, None);
+
+        // The repeated commands are undone in a single step.
+        press_keys(&mut app, "u");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "nexedit editor"
+        );
+    }
+
+    #[test]
+    fn handle_input_tracks_a_pending_count() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        press_keys(&mut app, "10");
+
+        assert_eq!(app.count, Some(10));
+    }
+
+    #[test]
+    fn handle_input_stops_repeating_commands_that_no_longer_change_anything() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\nthree");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        press_keys(&mut app, "999999j");
+
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 2, offset: 0 }
+        );
+
+        // Deleting at the end of the buffer moves the revision without
+        // changing anything, and repeating stops after the first of those.
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        let revision = app.view.revision(buffer);
+        press_keys(&mut app, "999999x");
+
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(buffer.data(), "one\ntwo\n");
+        assert_eq!(app.view.revision(buffer), revision + "three".len() + 1);
+    }
+
+    #[test]
+    fn handle_input_jumps_to_the_counted_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\nthree");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "3g");
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 2, offset: 0 }
+        );
+    }
+
+    #[test]
+    fn handle_input_runs_multi_key_sequences() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("(one)");
+        buffer.cursor.move_to(Position { line: 0, offset: 2 });
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "S");
+
+        assert_eq!(app.pending_keys, vec![Key::Char('S')]);
+
+        press_keys(&mut app, "d");
+
+        assert!(app.pending_keys.is_empty());
+        assert_eq!(app.workspace.current_buffer.as_ref().unwrap().data(), "one");
+    }
+
+    #[test]
+    fn handle_input_handles_the_latest_key_when_a_sequence_is_abandoned() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "Sx");
+
+        assert!(app.pending_keys.is_empty());
+        assert_eq!(app.workspace.current_buffer.as_ref().unwrap().data(), "ne");
+    }
+
+    #[test]
+    fn handle_key_timeout_replays_held_keys_one_at_a_time() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one two");
+        app.workspace.add_buffer(buffer);
+
+        // "S c" isn't bound on its own, but "c" is.
+        press_keys(&mut app, "Sc");
+        super::handle_key_timeout(&mut app).unwrap();
+
+        assert!(app.pending_keys.is_empty());
+        assert!(matches!(app.mode, Mode::Insert));
+        assert_eq!(app.workspace.current_buffer.as_ref().unwrap().data(), "two");
+    }
+
+    #[test]
+    fn repeat_last_change_replays_normal_mode_changes_with_their_counts() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit editor");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        press_keys(&mut app, "2xl.");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "xit editor"
+        );
+    }
+
+    #[test]
+    fn repeat_last_change_replays_text_typed_in_insert_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo");
+        util::add_buffer(buffer, &mut app).unwrap();
+
+        press_keys(&mut app, "i- ");
+        super::handle_key(&mut app, Key::Esc).unwrap();
+        press_keys(&mut app, "jH2.");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "- one\n- - two"
+        );
+    }
+
+    #[test]
+    fn repeat_last_change_ignores_switching_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut first_buffer = Buffer::new();
+        first_buffer.insert("nexedit");
+        util::add_buffer(first_buffer, &mut app).unwrap();
+        let mut second_buffer = Buffer::new();
+        second_buffer.insert("editor");
+        util::add_buffer(second_buffer, &mut app).unwrap();
+
+        press_keys(&mut app, "x");
+        super::handle_key(&mut app, Key::Tab).unwrap();
+        press_keys(&mut app, ".");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "exedit"
+        );
+    }
+
+    #[test]
+    fn play_macro_replays_recorded_keys_with_a_count() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\nthree\nfour");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "WaxjWW");
+
+        assert_eq!(app.macros.register('a'), Some(&[Key::Char('x'), Key::Char('j')][..]));
+
+        press_keys(&mut app, "2@a");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "ne\nwo\nhree\nfour"
+        );
+    }
+
+    #[test]
+    fn play_macro_on_lines_replays_the_macro_on_each_selected_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\nthree");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "Wbi- ");
+        super::handle_key(&mut app, Key::Esc).unwrap();
+        press_keys(&mut app, "WWjVj@b");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "- one\n- two\n- three"
+        );
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
+    #[test]
+    fn play_macro_on_lines_undoes_as_a_single_step() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\nthree");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "Wbi- ");
+        super::handle_key(&mut app, Key::Esc).unwrap();
+        press_keys(&mut app, "WWjVj@bu");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "- one\ntwo\nthree"
+        );
+    }
+
+    #[test]
+    fn select_register_directs_copies_and_pastes_to_a_named_register() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "\"ayj\"ap");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "one\ntwo\none\n"
+        );
+        assert_eq!(app.clipboard.selected_register(), None);
+    }
+
+    #[test]
+    fn select_register_only_applies_to_the_next_command() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo");
+        app.workspace.add_buffer(buffer);
+
+        press_keys(&mut app, "\"aj");
+
+        assert_eq!(app.clipboard.selected_register(), None);
+    }
+
+    #[test]
+    fn accepting_a_clipboard_history_entry_pastes_it_without_reordering_the_history() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\n");
+        app.workspace.add_buffer(buffer);
+        for (register, content) in [('a', "two\n"), ('b', "three\n")] {
+            app.clipboard.select_register(register).unwrap();
+            app.clipboard
+                .set_content(ClipboardContent::Block(content.to_string()))
+                .unwrap();
+        }
+
+        super::switch_to_clipboard_history_mode(&mut app).unwrap();
+        commands::search_select::select_next(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "one\ntwo\n"
+        );
+        assert_eq!(
+            app.clipboard.history().collect::<Vec<_>>(),
+            vec![
+                &ClipboardContent::Block("three\n".to_string()),
+                &ClipboardContent::Block("two\n".to_string())
+            ]
+        );
+    }
+
+    fn add_buffer_with_path(app: &mut Application, path: &str) {
+        let mut buffer = Buffer::new();
+        buffer.path = Some(app.workspace.path.join(path));
+        app.workspace.add_buffer(buffer);
+        app.track_buffer_use();
+    }
+
+    fn buffer_list_entries(app: &Application) -> Vec<String> {
+        match app.mode {
+            Mode::BufferList(ref mode) => mode.results().map(|entry| entry.path.clone()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn switch_to_buffer_list_mode_lists_recently_used_buffers_first() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        add_buffer_with_path(&mut app, "one");
+        add_buffer_with_path(&mut app, "two");
+        add_buffer_with_path(&mut app, "three");
+        app.workspace.next_buffer();
+        app.track_buffer_use();
+
+        super::switch_to_buffer_list_mode(&mut app).unwrap();
+
+        assert_eq!(buffer_list_entries(&app), vec!["one", "three", "two"]);
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("one")));
+    }
+
+    #[test]
+    fn buffer_list_mode_switches_to_and_closes_the_selected_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        add_buffer_with_path(&mut app, "one");
+        add_buffer_with_path(&mut app, "two");
+
+        super::switch_to_buffer_list_mode(&mut app).unwrap();
+        commands::search_select::select_next(&mut app).unwrap();
+        commands::search_select::close_selected(&mut app).unwrap();
+
+        assert_eq!(buffer_list_entries(&app), vec!["two"]);
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("two")));
+
+        add_buffer_with_path(&mut app, "three");
+        app.workspace.next_buffer();
+        super::switch_to_buffer_list_mode(&mut app).unwrap();
+        commands::search_select::select_next(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("three")));
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
+    #[test]
+    fn recent_files_mode_reopens_files_outside_the_workspace() {
+        let path = env::temp_dir().join(format!("nexedit_recent_file_{}", process::id()));
+        fs::write(&path, "recent").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.recent_files = History::new(None);
+        app.recent_files
+            .push(path.canonicalize().unwrap().to_string_lossy())
+            .unwrap();
+
+        super::switch_to_recent_files_mode(&mut app).unwrap();
+        let result = commands::search_select::accept(&mut app);
+        fs::remove_file(&path).unwrap();
+        result.unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "recent"
+        );
+    }
 }
@@ -1,14 +1,19 @@
 use crate::commands::{self, Result};
 use crate::errors::*;
 use crate::input::Key;
+use crate::models::application::modes::select_block::{column_of, offset_of};
 use crate::models::application::modes::ConfirmMode;
 use crate::models::application::{Application, ClipboardContent, Mode};
 use crate::util;
 use crate::util::token::{adjacent_token_position, Direction};
+use regex::Regex;
 use scribe::buffer::{Buffer, Position, Range, Token};
 use std::io::Write;
 use std::mem;
 use std::process::Stdio;
+use std::str::FromStr;
+use syntect::highlighting::ScopeSelectors;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn save(app: &mut Application) -> Result {
     remove_trailing_whitespace(app)?;
@@ -60,11 +65,15 @@ pub fn reload(app: &mut Application) -> Result {
 }
 
 pub fn delete(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .delete();
+    util::at_each_cursor(app, |app| {
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?
+            .delete();
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
@@ -112,8 +121,10 @@ pub fn delete_current_token(app: &mut Application) -> Result {
     Ok(())
 }
 
-
+// Secondary cursors aren't kept in step with edits made outside of
+// util::at_each_cursor, so commands like this one remove them instead.
 pub fn delete_current_line(app: &mut Application) -> Result {
+    app.cursors.clear();
     commands::application::switch_to_select_line_mode(app)?;
     commands::selection::copy_and_delete(app)?;
     commands::application::switch_to_normal_mode(app)?;
@@ -260,47 +271,114 @@ pub fn close_others_confirm(app: &mut Application) -> Result {
 }
 
 pub fn backspace(app: &mut Application) -> Result {
-    let mut outdent = false;
-
-    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        if buffer.cursor.offset == 0 {
-            buffer.cursor.move_up();
-            buffer.cursor.move_to_end_of_line();
-            buffer.delete();
-        } else {
-            let data = buffer.data();
-            let current_line = data
-                .lines()
-                .nth(buffer.cursor.line)
-                .ok_or(CURRENT_LINE_MISSING)?;
-            if current_line.chars().all(|c| c.is_whitespace()) {
-                outdent = true
-            } else {
-                buffer.cursor.move_left();
+    util::at_each_cursor(app, |app| {
+        let mut outdent = false;
+        let auto_pair = auto_pair_enabled(app)?;
+
+        if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+            if buffer.cursor.offset == 0 {
+                buffer.cursor.move_up();
+                buffer.cursor.move_to_end_of_line();
                 buffer.delete();
+            } else {
+                let data = buffer.data();
+                let current_line = data
+                    .lines()
+                    .nth(buffer.cursor.line)
+                    .ok_or(CURRENT_LINE_MISSING)?;
+                if current_line.chars().all(|c| c.is_whitespace()) {
+                    outdent = true
+                } else {
+                    let (before, after) = adjacent_chars(current_line, buffer.cursor.offset);
+                    buffer.cursor.move_left();
+                    buffer.delete();
+
+                    // Remove the other half of an empty pair.
+                    if auto_pair && before.and_then(closing_char).is_some_and(|c| Some(c) == after) {
+                        buffer.delete();
+                    }
+                }
             }
+        } else {
+            bail!(BUFFER_MISSING);
         }
-    } else {
-        bail!(BUFFER_MISSING);
-    }
 
-    if outdent {
-        commands::buffer::outdent_line(app)?;
-    }
+        if outdent {
+            commands::buffer::outdent_line(app)?;
+        }
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app)
 }
 
 pub fn insert_char(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        if let Some(Key::Char(character)) = *app.view.last_key() {
-            buffer.insert(character.to_string());
-            buffer.cursor.move_right();
+    util::at_each_cursor(app, |app| {
+        let character = match *app.view.last_key() {
+            Some(Key::Char(character)) => character,
+            _ => bail!("No character to insert"),
+        };
+        let closing = if auto_pair_enabled(app)? {
+            let buffer = app
+                .workspace
+                .current_buffer
+                .as_ref()
+                .ok_or(BUFFER_MISSING)?;
+            let data = buffer.data();
+            let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+            let (before, after) = adjacent_chars(line, buffer.cursor.offset);
+
+            // Type over closing characters rather than doubling them up.
+            if after == Some(character) && is_closing_char(character) {
+                app.workspace
+                    .current_buffer
+                    .as_mut()
+                    .ok_or(BUFFER_MISSING)?
+                    .cursor
+                    .move_right();
+                return Ok(());
+            }
+
+            match closing_char(character) {
+                Some(closing) if should_close(app, character, before, after)? => Some(closing),
+                _ => None,
+            }
         } else {
-            bail!("No character to insert");
+            None
+        };
+
+        // A broken indent pattern shouldn't get in the way of typing.
+        let decrease = {
+            let buffer = app
+                .workspace
+                .current_buffer
+                .as_ref()
+                .ok_or(BUFFER_MISSING)?;
+
+            app.preferences
+                .borrow()
+                .decrease_indent_pattern(buffer.path.as_ref())
+                .unwrap_or(None)
+        };
+
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let prefix = line_prefix(buffer);
+        buffer.insert(character.to_string());
+        buffer.cursor.move_right();
+        if let Some(closing) = closing {
+            buffer.insert(closing.to_string());
         }
-    } else {
-        bail!(BUFFER_MISSING);
-    }
+
+        if decrease.is_some_and(|pattern| completes_outdent(&pattern, &prefix, character)) {
+            outdent_line(app)?;
+        }
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
@@ -345,37 +423,86 @@ pub fn display_current_scope(app: &mut Application) -> Result {
 }
 
 pub fn insert_newline(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        buffer.insert("\n");
-
-        let position = buffer.cursor.clone();
-        buffer.cursor.move_down();
-        buffer.cursor.move_to_start_of_line();
-
-        let data = buffer.data();
-        let end_of_current_line = data
-            .lines()
-            .nth(position.line)
-            .map(|l| (l.as_ptr() as usize) + l.len())
-            .unwrap();
-        let offset = end_of_current_line - (data.as_str().as_ptr() as usize);
-        let (previous_content, _) = data.split_at(offset);
+    util::at_each_cursor(app, |app| {
+        let (increase, decrease, tab_content) = {
+            let buffer = app
+                .workspace
+                .current_buffer
+                .as_ref()
+                .ok_or(BUFFER_MISSING)?;
+            let preferences = app.preferences.borrow();
+            let path = buffer.path.as_ref();
 
-        let nearest_non_blank_line = previous_content.lines().rev().find(|line| !line.is_empty());
-        let indent_content = match nearest_non_blank_line {
-            Some(line) => line.chars().take_while(|&c| c.is_whitespace()).collect(),
-            None => String::new(),
+            (
+                preferences.increase_indent_pattern(path).unwrap_or(None),
+                preferences.decrease_indent_pattern(path).unwrap_or(None),
+                preferences.tab_content(path),
+            )
         };
 
-        let indent_length = indent_content.chars().count();
-        buffer.insert(indent_content);
-        buffer.cursor.move_to(Position {
-            line: position.line + 1,
-            offset: indent_length,
+        // Ending a line with a closing keyword outdents it, like typing a space would.
+        let outdent = decrease.as_ref().is_some_and(|pattern| {
+            app.workspace
+                .current_buffer
+                .as_ref()
+                .is_some_and(|buffer| completes_outdent(pattern, &line_prefix(buffer), '\n'))
         });
-    } else {
-        bail!(BUFFER_MISSING);
-    }
+        if outdent {
+            outdent_line(app)?;
+        }
+
+        if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+            buffer.insert("\n");
+
+            let position = buffer.cursor.clone();
+            buffer.cursor.move_down();
+            buffer.cursor.move_to_start_of_line();
+
+            let data = buffer.data();
+            let end_of_current_line = data
+                .lines()
+                .nth(position.line)
+                .map(|l| (l.as_ptr() as usize) + l.len())
+                .unwrap();
+            let offset = end_of_current_line - (data.as_str().as_ptr() as usize);
+            let (previous_content, _) = data.split_at(offset);
+
+            let nearest_non_blank_line = previous_content.lines().rev().find(|line| !line.is_empty());
+            let base_indent: String = match nearest_non_blank_line {
+                Some(line) => line.chars().take_while(|&c| c.is_whitespace()).collect(),
+                None => String::new(),
+            };
+            let increased = match (nearest_non_blank_line, increase) {
+                (Some(line), Some(pattern)) => pattern.is_match(line),
+                _ => false,
+            };
+            let indent_content = if increased {
+                format!("{}{}", base_indent, tab_content)
+            } else {
+                base_indent.clone()
+            };
+
+            // Splitting a pair (e.g. "{}") moves its closing half
+            // onto its own line, leaving the cursor between them.
+            let remaining_line = data.lines().nth(position.line + 1).unwrap_or("");
+            let closes_pair = increased
+                && decrease.is_some_and(|pattern| pattern.is_match(remaining_line));
+
+            let indent_length = indent_content.chars().count();
+            buffer.insert(indent_content);
+            buffer.cursor.move_to(Position {
+                line: position.line + 1,
+                offset: indent_length,
+            });
+            if closes_pair {
+                buffer.insert(format!("\n{}", base_indent));
+            }
+        } else {
+            bail!(BUFFER_MISSING);
+        }
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
@@ -639,6 +766,11 @@ pub fn start_command_group(app: &mut Application) -> Result {
 }
 
 pub fn end_command_group(app: &mut Application) -> Result {
+    // Held groups are ended when they're released.
+    if app.operation_group_held() {
+        return Ok(());
+    }
+
     app.workspace
         .current_buffer
         .as_mut()
@@ -649,6 +781,9 @@ pub fn end_command_group(app: &mut Application) -> Result {
 }
 
 pub fn undo(app: &mut Application) -> Result {
+    // Undoing isn't a change that can be repeated.
+    app.changes.cancel();
+    app.cursors.clear();
     app.workspace
         .current_buffer
         .as_mut()
@@ -658,6 +793,9 @@ pub fn undo(app: &mut Application) -> Result {
 }
 
 pub fn redo(app: &mut Application) -> Result {
+    // Redoing isn't a change that can be repeated, either.
+    app.changes.cancel();
+    app.cursors.clear();
     app.workspace
         .current_buffer
         .as_mut()
@@ -667,8 +805,15 @@ pub fn redo(app: &mut Application) -> Result {
 }
 
 pub fn paste(app: &mut Application) -> Result {
+    paste_content(app, None)
+}
+
+/// Pastes the given content, or the clipboard's when there isn't any.
+pub(crate) fn paste_content(app: &mut Application, content: Option<ClipboardContent>) -> Result {
+    app.cursors.clear();
+    let tab_width = app.tab_width();
     let insert_below = match app.mode {
-        Mode::Select(_) | Mode::SelectLine(_) | Mode::Search(_) => {
+        Mode::Select(_) | Mode::SelectLine(_) | Mode::SelectBlock(_) | Mode::Search(_) => {
             commands::selection::delete(app)
                 .chain_err(|| "Couldn't delete selection prior to pasting.")?;
             false
@@ -676,8 +821,12 @@ pub fn paste(app: &mut Application) -> Result {
         _ => true,
     };
 
+    let content = match content {
+        Some(content) => content,
+        None => app.clipboard.get_content().clone(),
+    };
     if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        match *app.clipboard.get_content() {
+        match content {
             ClipboardContent::Inline(ref content) => buffer.insert(content.clone()),
             ClipboardContent::Block(ref content) => {
                 let original_cursor_position = *buffer.cursor.clone();
@@ -707,6 +856,7 @@ pub fn paste(app: &mut Application) -> Result {
                     buffer.insert(content.clone());
                 }
             }
+            ClipboardContent::Rectangle(ref content) => paste_rectangle(buffer, content, tab_width),
             ClipboardContent::None => (),
         }
     } else {
@@ -717,7 +867,42 @@ pub fn paste(app: &mut Application) -> Result {
     Ok(())
 }
 
+// Inserts each row of the rectangle at the cursor's screen column on
+// successive lines, padding short lines and adding lines where needed.
+fn paste_rectangle(buffer: &mut Buffer, content: &str, tab_width: usize) {
+    let original_cursor_position = *buffer.cursor.clone();
+    let data = buffer.data();
+    let lines: Vec<&str> = data.split('\n').collect();
+    let column = column_of(
+        lines[original_cursor_position.line],
+        original_cursor_position.offset,
+        tab_width,
+    );
+
+    buffer.start_operation_group();
+    for (index, row) in content.split('\n').enumerate() {
+        let line = original_cursor_position.line + index;
+
+        if let Some(line_content) = lines.get(line) {
+            let width = column_of(line_content, usize::MAX, tab_width);
+            buffer.cursor.move_to(Position {
+                line,
+                offset: offset_of(line_content, column, tab_width),
+            });
+            let padding = " ".repeat(column.saturating_sub(width));
+            buffer.insert(format!("{}{}", padding, row));
+        } else {
+            buffer.cursor.move_to_last_line();
+            buffer.cursor.move_to_end_of_line();
+            buffer.insert(format!("\n{}{}", " ".repeat(column), row));
+        }
+    }
+    buffer.end_operation_group();
+    buffer.cursor.move_to(original_cursor_position);
+}
+
 pub fn paste_above(app: &mut Application) -> Result {
+    app.cursors.clear();
     let buffer = app
         .workspace
         .current_buffer
@@ -828,20 +1013,22 @@ pub fn ensure_trailing_newline(app: &mut Application) -> Result {
 }
 
 pub fn insert_tab(app: &mut Application) -> Result {
-    let buffer = app
-        .workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?;
-    let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
-    let tab_content_width = tab_content.chars().count();
-    buffer.insert(tab_content);
+    util::at_each_cursor(app, |app| {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
+        let tab_content_width = tab_content.chars().count();
+        buffer.insert(tab_content);
 
-    for _ in 0..tab_content_width {
-        buffer.cursor.move_right();
-    }
+        for _ in 0..tab_content_width {
+            buffer.cursor.move_right();
+        }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 pub fn format(app: &mut Application) -> Result {
@@ -891,9 +1078,128 @@ pub fn format(app: &mut Application) -> Result {
     }
 }
 
+// The cursor's line up to the cursor, read without copying the rest of the buffer.
+fn line_prefix(buffer: &Buffer) -> String {
+    let start = Position {
+        line: buffer.cursor.line,
+        offset: 0,
+    };
+
+    buffer
+        .read(&Range::new(start, *buffer.cursor))
+        .unwrap_or_default()
+}
+
+// Whether typing the character after the prefix finishes a closing bracket or
+// keyword at the start of the line. A trailing word character stands in for
+// whatever's typed next, so that keywords only match once they're complete
+// (e.g. "end" isn't outdented until it's clear that it isn't "endpoint").
+fn completes_outdent(pattern: &Regex, prefix: &str, character: char) -> bool {
+    !pattern.is_match(&format!("{}_", prefix))
+        && pattern.is_match(&format!("{}{}_", prefix, character))
+}
+
+fn auto_pair_enabled(app: &Application) -> std::result::Result<bool, Error> {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+
+    Ok(app.preferences.borrow().auto_pair(buffer.path.as_ref()))
+}
+
+// The characters on either side of an offset in a line.
+fn adjacent_chars(line: &str, offset: usize) -> (Option<char>, Option<char>) {
+    let mut graphemes = line.graphemes(true).skip(offset.saturating_sub(1));
+    let before = if offset > 0 {
+        graphemes.next().and_then(|g| g.chars().next())
+    } else {
+        None
+    };
+    let after = graphemes.next().and_then(|g| g.chars().next());
+
+    (before, after)
+}
+
+fn closing_char(opening: char) -> Option<char> {
+    match opening {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '"' => Some('"'),
+        '\'' => Some('\''),
+        _ => None,
+    }
+}
+
+fn is_closing_char(character: char) -> bool {
+    matches!(character, ')' | ']' | '}' | '"' | '\'')
+}
+
+// Pairs are only closed ahead of whitespace or other closing characters,
+// and never inside of strings or comments. Quotes directly following a
+// word (e.g. an apostrophe) aren't closed either.
+fn should_close(
+    app: &Application,
+    opening: char,
+    before: Option<char>,
+    after: Option<char>,
+) -> std::result::Result<bool, Error> {
+    if after.is_some_and(|c| !c.is_whitespace() && !is_closing_char(c)) {
+        return Ok(false);
+    }
+    if closing_char(opening) == Some(opening) && before.is_some_and(char::is_alphanumeric) {
+        return Ok(false);
+    }
+
+    Ok(!in_string_or_comment(app)?)
+}
+
+// Whether the character preceding the cursor is part of a string or
+// comment, excluding the delimiter that closes it.
+fn in_string_or_comment(app: &Application) -> std::result::Result<bool, Error> {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let tokens = app
+        .workspace
+        .current_buffer_tokens()
+        .chain_err(|| BUFFER_TOKENS_FAILED)?;
+    let mut token_iter = tokens.iter().chain_err(|| BUFFER_PARSE_FAILED)?;
+    let selectors = ScopeSelectors::from_str(
+        "string - punctuation.definition.string.end, comment - punctuation.definition.comment.end",
+    )
+    .chain_err(|| "Failed to parse scope selectors")?;
+    let target = Position {
+        line: buffer.cursor.line,
+        offset: buffer.cursor.offset.saturating_sub(1),
+    };
+
+    let mut scope = None;
+    for token in &mut token_iter {
+        if let Token::Lexeme(lexeme) = token {
+            if lexeme.position > target {
+                break;
+            }
+
+            scope = Some(lexeme.scope);
+        }
+    }
+
+    if let Some(e) = token_iter.error {
+        Err(e).chain_err(|| BUFFER_PARSE_FAILED)?;
+    }
+
+    Ok(scope.is_some_and(|scope| selectors.does_match(scope.as_slice()).is_some()))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
     use crate::models::application::{ClipboardContent, Mode, Preferences};
     use crate::models::Application;
     use scribe::buffer::Position;
@@ -901,7 +1207,7 @@ mod tests {
     use std::env;
     use std::fs::File;
     use std::io::Write;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
     use yaml_rust::yaml::YamlLoader;
 
     #[test]
@@ -932,6 +1238,98 @@ mod tests {
         );
     }
 
+    #[test]
+    fn insert_newline_increases_indentation_after_an_opening_bracket() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("  fn main() {}");
+        buffer.cursor.move_to(Position { line: 0, offset: 13 });
+        app.workspace.add_buffer(buffer);
+
+        super::insert_newline(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(buffer.data(), "  fn main() {\n    \n  }");
+        assert_eq!(*buffer.cursor, Position { line: 1, offset: 4 });
+    }
+
+    #[test]
+    fn insert_newline_uses_type_specific_indent_patterns() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("def main():");
+        buffer.path = Some(PathBuf::from("main.py"));
+        buffer.cursor.move_to(Position { line: 0, offset: 11 });
+        app.workspace.add_buffer(buffer);
+
+        super::insert_newline(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "def main():\n  "
+        );
+    }
+
+    #[test]
+    fn insert_char_outdents_closing_brackets() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("{\n    ");
+        buffer.cursor.move_to(Position { line: 1, offset: 4 });
+        app.workspace.add_buffer(buffer);
+
+        app.view.last_key = Some(Key::Char('}'));
+        super::insert_char(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('}'));
+        super::insert_char(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(buffer.data(), "{\n  }}");
+        assert_eq!(*buffer.cursor, Position { line: 1, offset: 4 });
+    }
+
+    #[test]
+    fn insert_char_outdents_keywords_once_they_are_complete() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("if a\n    b\n    \n    ");
+        buffer.path = Some(PathBuf::from("main.rb"));
+        buffer.cursor.move_to(Position { line: 2, offset: 4 });
+        app.workspace.add_buffer(buffer);
+
+        type_chars(&mut app, "endpoint");
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .cursor
+            .move_to(Position { line: 3, offset: 4 });
+        type_chars(&mut app, "end");
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "if a\n    b\n    endpoint\n    end"
+        );
+
+        super::insert_newline(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "if a\n    b\n    endpoint\n  end\n  "
+        );
+    }
+
+    #[test]
+    fn insert_char_ignores_invalid_indent_patterns() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let config = YamlLoader::load_from_str("indent:\n  decrease: '('").unwrap();
+        app.preferences
+            .replace(Preferences::new(config.into_iter().nth(0)));
+        app.workspace.add_buffer(Buffer::new());
+
+        type_chars(&mut app, "a}");
+
+        assert_eq!(app.workspace.current_buffer.as_ref().unwrap().data(), "a}");
+    }
+
     #[test]
     fn insert_newline_uses_nearest_line_indentation_when_current_line_blank() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1505,6 +1903,62 @@ mod tests {
         );
     }
 
+    fn auto_pair_app(data: &str, position: Position) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let config = YamlLoader::load_from_str("auto_pair: true").unwrap();
+        app.preferences
+            .replace(Preferences::new(config.into_iter().nth(0)));
+
+        let mut buffer = Buffer::new();
+        buffer.insert(data);
+        buffer.cursor.move_to(position);
+        buffer.syntax_definition = app.workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    fn type_chars(app: &mut Application, chars: &str) {
+        for c in chars.chars() {
+            app.view.last_key = Some(Key::Char(c));
+            super::insert_char(app).unwrap();
+        }
+    }
+
+    #[test]
+    fn insert_char_closes_pairs_and_types_over_closing_characters() {
+        let mut app = auto_pair_app("", Position { line: 0, offset: 0 });
+
+        type_chars(&mut app, "f(\"");
+        assert_eq!(app.workspace.current_buffer.as_ref().unwrap().data(), "f(\"\")");
+
+        type_chars(&mut app, "a\")");
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(buffer.data(), "f(\"a\")");
+        assert_eq!(*buffer.cursor, Position { line: 0, offset: 6 });
+    }
+
+    #[test]
+    fn insert_char_does_not_close_pairs_inside_strings() {
+        let mut app = auto_pair_app("let x = \"a b\";", Position { line: 0, offset: 10 });
+
+        type_chars(&mut app, "(");
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "let x = \"a( b\";"
+        );
+    }
+
+    #[test]
+    fn backspace_removes_empty_pairs_when_auto_pairing() {
+        let mut app = auto_pair_app("f()", Position { line: 0, offset: 2 });
+
+        commands::buffer::backspace(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer.as_ref().unwrap().data(), "f");
+    }
+
     #[test]
     fn merge_next_line_joins_current_and_next_lines_with_a_space() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -2047,4 +2501,16 @@ mod tests {
             Position { line: 2, offset: 0 }
         );
     }
+
+    #[test]
+    fn paste_rectangle_inserts_rows_at_the_same_column_and_pads_short_lines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("\tnexedit\na");
+        buffer.cursor.move_to(Position { line: 0, offset: 1 });
+
+        super::paste_rectangle(&mut buffer, "x\ny\nz", 2);
+
+        assert_eq!(buffer.data(), "\txnexedit\na y\n  z");
+        assert_eq!(*buffer.cursor, Position { line: 0, offset: 1 });
+    }
 }
@@ -2,66 +2,95 @@ use super::{application, buffer};
 use crate::commands::{self, Result};
 use crate::errors::*;
 use crate::models::application::Application;
+use crate::util;
+use crate::util::brackets;
+use crate::util::fold;
 use crate::util::token::{adjacent_token_position, Direction};
-use scribe::buffer::Position;
+use scribe::buffer::{Buffer, Cursor, LineRange, Position};
 
 pub fn move_up(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_up();
+    util::at_each_cursor(app, |app| {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let folds = app.view.folds(buffer)?;
+        move_past_folds(buffer, &folds, Cursor::move_up);
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_down(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_down();
+    util::at_each_cursor(app, |app| {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let folds = app.view.folds(buffer)?;
+        move_past_folds(buffer, &folds, Cursor::move_down);
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_left(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_left();
+    util::at_each_cursor(app, |app| {
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?
+            .cursor
+            .move_left();
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_right(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_right();
+    util::at_each_cursor(app, |app| {
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?
+            .cursor
+            .move_right();
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_to_start_of_line(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_to_start_of_line();
+    util::at_each_cursor(app, |app| {
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?
+            .cursor
+            .move_to_start_of_line();
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_to_end_of_line(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer
-        .as_mut()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_to_end_of_line();
+    util::at_each_cursor(app, |app| {
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?
+            .cursor
+            .move_to_end_of_line();
+
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
@@ -117,6 +146,37 @@ pub fn move_to_first_word_of_line(app: &mut Application) -> Result {
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+pub fn move_to_matching_bracket(app: &mut Application) -> Result {
+    let position = {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_ref()
+            .ok_or(BUFFER_MISSING)?;
+        let tokens = app
+            .workspace
+            .current_buffer_tokens()
+            .chain_err(|| BUFFER_TOKENS_FAILED)?;
+        let brackets = brackets::from_tokens(tokens.iter().chain_err(|| BUFFER_PARSE_FAILED)?);
+        let index = brackets
+            .iter()
+            .position(|bracket| bracket.position == *buffer.cursor)
+            .ok_or("No bracket at the cursor")?;
+
+        brackets::find_match(&brackets, index)
+            .map(|index| brackets[index].position)
+            .ok_or("No matching bracket found")?
+    };
+
+    app.workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?
+        .cursor
+        .move_to(position);
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
 pub fn insert_at_end_of_line(app: &mut Application) -> Result {
     move_to_end_of_line(app)?;
     application::switch_to_insert_mode(app)?;
@@ -168,50 +228,95 @@ pub fn insert_with_newline_above(app: &mut Application) -> Result {
 }
 
 pub fn move_to_start_of_previous_token(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        let position = adjacent_token_position(buffer, false, Direction::Backward)
-            .ok_or("Couldn't find previous token")?;
+    util::at_each_cursor(app, |app| {
+        if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+            let position = adjacent_token_position(buffer, false, Direction::Backward)
+                .ok_or("Couldn't find previous token")?;
+
+            buffer.cursor.move_to(position);
+        } else {
+            bail!(BUFFER_MISSING);
+        }
 
-        buffer.cursor.move_to(position);
-    } else {
-        bail!(BUFFER_MISSING);
-    }
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_to_start_of_next_token(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        let position = adjacent_token_position(buffer, false, Direction::Forward)
-            .ok_or("Couldn't find next token")?;
+    util::at_each_cursor(app, |app| {
+        if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+            let position = adjacent_token_position(buffer, false, Direction::Forward)
+                .ok_or("Couldn't find next token")?;
+
+            buffer.cursor.move_to(position);
+        } else {
+            bail!(BUFFER_MISSING);
+        }
 
-        buffer.cursor.move_to(position);
-    } else {
-        bail!(BUFFER_MISSING);
-    }
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_to_end_of_current_token(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
-        let position = adjacent_token_position(buffer, true, Direction::Forward)
-            .ok_or("Couldn't find next token")?;
+    util::at_each_cursor(app, |app| {
+        if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+            let position = adjacent_token_position(buffer, true, Direction::Forward)
+                .ok_or("Couldn't find next token")?;
+
+            buffer.cursor.move_to(position);
+        } else {
+            bail!(BUFFER_MISSING);
+        }
 
-        buffer.cursor.move_to(position);
-    } else {
-        bail!(BUFFER_MISSING);
-    }
+        Ok(())
+    })?;
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+pub fn remove_secondary_cursors(app: &mut Application) -> Result {
+    app.cursors.clear();
+
+    Ok(())
+}
+
 pub fn append_to_current_token(app: &mut Application) -> Result {
     move_to_end_of_current_token(app)?;
     application::switch_to_insert_mode(app)
 }
 
+// Keeps moving the cursor until it reaches a line that isn't folded away,
+// leaving it in place if there isn't one in that direction.
+fn move_past_folds<F>(buffer: &mut Buffer, folds: &[LineRange], move_cursor: F)
+where
+    F: Fn(&mut Cursor),
+{
+    let position = *buffer.cursor;
+    let mut moved = false;
+
+    loop {
+        let line = buffer.cursor.line;
+        move_cursor(&mut buffer.cursor);
+        if buffer.cursor.line == line {
+            break;
+        }
+        moved = true;
+
+        if !fold::hidden(folds, buffer.cursor.line) {
+            return;
+        }
+    }
+
+    if moved {
+        buffer.cursor.move_to(position);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::models::application::Application;
-    use scribe::buffer::Position;
+    use scribe::buffer::{LineRange, Position};
     use scribe::Buffer;
 
     #[test]
@@ -363,6 +468,70 @@ mod tests {
         });
     }
 
+    #[test]
+    fn move_to_matching_bracket_moves_between_pairs() {
+        let mut app = set_up_application("fn main() {\n    call(\")\");\n}");
+        let syntax = app
+            .workspace
+            .syntax_set
+            .find_syntax_by_extension("rs")
+            .cloned();
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .syntax_definition = syntax;
+
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .cursor
+            .move_to(Position { line: 1, offset: 8 });
+        super::move_to_matching_bracket(&mut app).unwrap();
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position {
+                line: 1,
+                offset: 12
+            }
+        );
+
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .cursor
+            .move_to(Position { line: 2, offset: 0 });
+        super::move_to_matching_bracket(&mut app).unwrap();
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position {
+                line: 0,
+                offset: 10
+            }
+        );
+    }
+
+    #[test]
+    fn move_up_and_down_skip_folded_lines() {
+        let mut app = set_up_application("a\n  b\n  c\nd");
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        app.view.fold(buffer, LineRange::new(0, 3)).unwrap();
+
+        super::move_down(&mut app).unwrap();
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 3, offset: 0 }
+        );
+
+        super::move_up(&mut app).unwrap();
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 0, offset: 0 }
+        );
+    }
+
     fn set_up_application(content: &str) -> Application {
         let mut app = Application::new(&Vec::new()).unwrap();
         let mut buffer = Buffer::new();
@@ -0,0 +1,184 @@
+use crate::commands::{self, Result};
+use crate::errors::*;
+use crate::models::application::Application;
+use crate::util::{brackets, fold};
+use scribe::buffer::Position;
+
+/// Folds the syntax region (e.g. function) containing the cursor,
+/// falling back to the surrounding block of indented lines.
+pub fn fold(app: &mut Application) -> Result {
+    let line = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?
+        .cursor
+        .line;
+    let selectors = {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_ref()
+            .ok_or(BUFFER_MISSING)?;
+        app.preferences.borrow().fold_scope(buffer.path.as_ref())?
+    };
+    let scope_block = match selectors {
+        Some(selectors) => {
+            let tokens = app
+                .workspace
+                .current_buffer_tokens()
+                .chain_err(|| BUFFER_TOKENS_FAILED)?;
+            let brackets = brackets::from_tokens(tokens.iter().chain_err(|| BUFFER_PARSE_FAILED)?);
+            let token_iter = tokens.iter().chain_err(|| BUFFER_PARSE_FAILED)?;
+
+            fold::scope_block(token_iter, &brackets, &selectors, line)
+        }
+        None => None,
+    };
+
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?;
+    let range = scope_block
+        .or_else(|| fold::indentation_block(&buffer.data(), line))
+        .ok_or("No foldable block found at the cursor")?;
+
+    // The cursor can't remain on a line that's being hidden.
+    if range.start() != line {
+        buffer.cursor.move_to(Position {
+            line: range.start(),
+            offset: 0,
+        });
+    }
+    app.view.fold(buffer, range)?;
+
+    commands::view::scroll_to_cursor(app)
+}
+
+pub fn unfold(app: &mut Application) -> Result {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+
+    if !app.view.unfold(buffer, buffer.cursor.line)? {
+        bail!("No fold found at the cursor");
+    }
+
+    Ok(())
+}
+
+pub fn toggle(app: &mut Application) -> Result {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+
+    if app.view.unfold(buffer, buffer.cursor.line)? {
+        Ok(())
+    } else {
+        fold(app)
+    }
+}
+
+pub fn unfold_all(app: &mut Application) -> Result {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+
+    app.view.unfold_all(buffer)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::Application;
+    use scribe::buffer::{LineRange, Position};
+    use scribe::Buffer;
+
+    fn app_with_buffer(data: &str, position: Position) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(data);
+        buffer.cursor.move_to(position);
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    fn folds(app: &mut Application) -> Vec<LineRange> {
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+
+        app.view.folds(buffer).unwrap()
+    }
+
+    #[test]
+    fn fold_hides_the_indented_block_around_the_cursor() {
+        let mut app = app_with_buffer("a:\n  b\n  c\nd", Position { line: 2, offset: 1 });
+
+        super::fold(&mut app).unwrap();
+
+        assert_eq!(folds(&mut app), vec![LineRange::new(0, 3)]);
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 0, offset: 0 }
+        );
+    }
+
+    #[test]
+    fn fold_prefers_syntax_regions() {
+        let mut app = app_with_buffer(
+            "fn main() {\n    run();\n}\n",
+            Position { line: 0, offset: 0 },
+        );
+        let syntax = app
+            .workspace
+            .syntax_set
+            .find_syntax_by_extension("rs")
+            .cloned();
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .syntax_definition = syntax;
+
+        super::fold(&mut app).unwrap();
+
+        assert_eq!(folds(&mut app), vec![LineRange::new(0, 3)]);
+    }
+
+    #[test]
+    fn toggle_unfolds_the_fold_at_the_cursor() {
+        let mut app = app_with_buffer("a:\n  b\nc", Position { line: 0, offset: 0 });
+
+        super::toggle(&mut app).unwrap();
+        assert_eq!(folds(&mut app), vec![LineRange::new(0, 2)]);
+
+        super::toggle(&mut app).unwrap();
+        assert!(folds(&mut app).is_empty());
+    }
+
+    #[test]
+    fn moving_the_cursor_into_a_fold_unfolds_it() {
+        let mut app = app_with_buffer("a:\n  b\nc", Position { line: 0, offset: 0 });
+        super::fold(&mut app).unwrap();
+
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .cursor
+            .move_to(Position { line: 1, offset: 0 });
+        commands::view::scroll_to_cursor(&mut app).unwrap();
+
+        assert!(folds(&mut app).is_empty());
+    }
+}
@@ -10,6 +10,7 @@ pub fn accept_input(app: &mut Application) -> Result {
             .input
             .parse::<usize>()
             .chain_err(|| "Couldn't parse a line number from the provided input.")?;
+        let _ = app.line_jump_history.push(mode.input.as_str());
 
         if line_number > 0 {
             let buffer = app
@@ -77,12 +78,37 @@ pub fn pop_search_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn previous_history_entry(app: &mut Application) -> Result {
+    if let Mode::LineJump(ref mut mode) = app.mode {
+        if let Some(entry) = app.line_jump_history.previous(&mode.input) {
+            mode.input = entry.to_string();
+        }
+    } else {
+        bail!("Can't recall line jump history outside of line jump mode");
+    }
+
+    Ok(())
+}
+
+pub fn next_history_entry(app: &mut Application) -> Result {
+    if let Mode::LineJump(ref mut mode) = app.mode {
+        if let Some(entry) = app.line_jump_history.next() {
+            mode.input = entry.to_string();
+        }
+    } else {
+        bail!("Can't recall line jump history outside of line jump mode");
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::commands;
-    use crate::models::application::{Application, Mode};
+    use crate::models::application::{Application, History, Mode};
     use scribe::buffer::Position;
     use scribe::Buffer;
+    use std::path::PathBuf;
 
     #[test]
     fn accept_input_moves_cursor_to_requested_line_and_changes_modes() {
@@ -159,4 +185,25 @@ mod tests {
             _ => false,
         });
     }
+
+    #[test]
+    fn accept_input_jumps_even_if_the_history_cant_be_saved() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo");
+
+        app.workspace.add_buffer(buffer);
+        app.line_jump_history = History::new(Some(PathBuf::from("/nonexistent/nexedit/line_jump")));
+        commands::application::switch_to_line_jump_mode(&mut app).unwrap();
+        if let Mode::LineJump(ref mut mode) = app.mode {
+            mode.input = "2".to_string();
+        }
+        commands::line_jump::accept_input(&mut app).unwrap();
+
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 1, offset: 0 }
+        );
+        assert!(matches!(app.mode, Mode::Normal));
+    }
 }
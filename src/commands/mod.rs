@@ -6,14 +6,19 @@ pub mod application;
 pub mod buffer;
 pub mod confirm;
 pub mod cursor;
+pub mod fold;
 pub mod git;
 pub mod jump;
 pub mod line_jump;
+pub mod pane;
 pub mod path;
 pub mod preferences;
+pub mod replace;
 pub mod search;
 pub mod search_select;
 pub mod selection;
+pub mod session;
+pub mod surround;
 pub mod view;
 pub mod workspace;
 
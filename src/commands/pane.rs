@@ -0,0 +1,181 @@
+use crate::commands::{self, Result};
+use crate::models::application::Application;
+use crate::util;
+use crate::view::{Arrangement, Direction};
+
+// The percentage of its split by which a pane grows or shrinks.
+const RESIZE_AMOUNT: isize = 5;
+
+pub fn split_vertically(app: &mut Application) -> Result {
+    split(app, Arrangement::SideBySide)
+}
+
+pub fn split_horizontally(app: &mut Application) -> Result {
+    split(app, Arrangement::Stacked)
+}
+
+pub fn close(app: &mut Application) -> Result {
+    app.view.close_pane()?;
+
+    restore_focused_pane(app)
+}
+
+pub fn grow(app: &mut Application) -> Result {
+    app.view.resize_pane(RESIZE_AMOUNT)
+}
+
+pub fn shrink(app: &mut Application) -> Result {
+    app.view.resize_pane(-RESIZE_AMOUNT)
+}
+
+pub fn focus_left(app: &mut Application) -> Result {
+    focus(app, Direction::Left)
+}
+
+pub fn focus_right(app: &mut Application) -> Result {
+    focus(app, Direction::Right)
+}
+
+pub fn focus_up(app: &mut Application) -> Result {
+    focus(app, Direction::Up)
+}
+
+pub fn focus_down(app: &mut Application) -> Result {
+    focus(app, Direction::Down)
+}
+
+fn split(app: &mut Application, arrangement: Arrangement) -> Result {
+    app.view
+        .update_focused_pane(app.workspace.current_buffer.as_ref());
+    app.view.split_pane(arrangement);
+
+    // Now that the buffer is shared, both halves can track its line count.
+    app.view
+        .update_focused_pane(app.workspace.current_buffer.as_ref());
+
+    Ok(())
+}
+
+fn focus(app: &mut Application, direction: Direction) -> Result {
+    let pane = app
+        .view
+        .neighbouring_pane(direction)
+        .ok_or("No pane in that direction")?;
+    app.view
+        .update_focused_pane(app.workspace.current_buffer.as_ref());
+    app.view.focus_pane(pane)?;
+
+    restore_focused_pane(app)
+}
+
+// Brings up the buffer shown in the focused pane, with its cursor where
+// it was left. Secondary cursors don't survive moving between panes.
+fn restore_focused_pane(app: &mut Application) -> Result {
+    let pane = app.view.focused_pane();
+    let Some(id) = pane.buffer_id else {
+        return Ok(());
+    };
+    if !util::select_buffer(&mut app.workspace, id) {
+        return Ok(());
+    }
+
+    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+        buffer.cursor.move_to(pane.cursor);
+    }
+    app.cursors.clear();
+
+    commands::view::scroll_to_cursor(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::Application;
+    use crate::util;
+    use scribe::buffer::Position;
+    use scribe::Buffer;
+
+    fn buffer(data: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.insert(data);
+
+        buffer
+    }
+
+    fn cursor(app: &Application) -> Position {
+        *app.workspace.current_buffer.as_ref().unwrap().cursor
+    }
+
+    #[test]
+    fn panes_showing_the_same_buffer_keep_their_own_cursors() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(buffer("nexedit\nnexedit"));
+
+        super::split_vertically(&mut app).unwrap();
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .cursor
+            .move_to(Position { line: 1, offset: 2 });
+
+        super::focus_left(&mut app).unwrap();
+        assert_eq!(cursor(&app), Position { line: 0, offset: 0 });
+
+        super::focus_right(&mut app).unwrap();
+        assert_eq!(cursor(&app), Position { line: 1, offset: 2 });
+    }
+
+    #[test]
+    fn pane_cursors_follow_lines_added_in_another_pane() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        util::add_buffer(buffer("one\ntwo\nthree"), &mut app).unwrap();
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        buffer.cursor.move_to(Position { line: 2, offset: 1 });
+
+        super::split_vertically(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        buffer.cursor.move_to(Position { line: 0, offset: 0 });
+        buffer.insert("zero\n");
+
+        super::focus_left(&mut app).unwrap();
+        assert_eq!(cursor(&app), Position { line: 3, offset: 1 });
+    }
+
+    #[test]
+    fn focusing_a_pane_brings_up_its_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(buffer("first"));
+
+        super::split_horizontally(&mut app).unwrap();
+        app.workspace.add_buffer(buffer("second"));
+
+        super::focus_up(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "first"
+        );
+
+        super::focus_down(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "second"
+        );
+    }
+
+    #[test]
+    fn close_focuses_the_remaining_pane() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(buffer("first"));
+
+        super::split_vertically(&mut app).unwrap();
+        app.workspace.add_buffer(buffer("second"));
+        super::close(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "first"
+        );
+        assert!(super::close(&mut app).is_err());
+        assert!(super::focus_right(&mut app).is_err());
+    }
+}
@@ -31,6 +31,28 @@ pub fn pop_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn previous_history_entry(app: &mut Application) -> Result {
+    if let Mode::Path(ref mut mode) = app.mode {
+        if let Some(entry) = app.path_history.previous(&mode.input) {
+            mode.input = entry.to_string();
+        }
+    } else {
+        bail!("Cannot recall path history outside of path mode");
+    }
+    Ok(())
+}
+
+pub fn next_history_entry(app: &mut Application) -> Result {
+    if let Mode::Path(ref mut mode) = app.mode {
+        if let Some(entry) = app.path_history.next() {
+            mode.input = entry.to_string();
+        }
+    } else {
+        bail!("Cannot recall path history outside of path mode");
+    }
+    Ok(())
+}
+
 pub fn accept_path(app: &mut Application) -> Result {
     let save_on_accept = if let Mode::Path(ref mut mode) = app.mode {
         let current_buffer = app
@@ -42,6 +64,7 @@ pub fn accept_path(app: &mut Application) -> Result {
         if path_name.is_empty() {
             bail!("Please provide a non-empty path")
         }
+        let _ = app.path_history.push(path_name.as_str());
         current_buffer.path = Some(PathBuf::from(path_name));
         mode.save_on_accept
     } else {
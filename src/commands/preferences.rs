@@ -8,5 +8,10 @@ pub fn edit(app: &mut Application) -> Result {
 }
 
 pub fn reload(app: &mut Application) -> Result {
-    app.preferences.borrow_mut().reload()
+    app.preferences.borrow_mut().reload()?;
+
+    let backend = app.preferences.borrow().clipboard();
+    app.clipboard.set_backend(backend, app.view.terminal());
+
+    Ok(())
 }
@@ -0,0 +1,247 @@
+use crate::commands::{self, Result};
+use crate::errors::*;
+use crate::input::Key;
+use crate::models::application::{Application, Mode};
+
+pub fn push_char(app: &mut Application) -> Result {
+    let key = app
+        .view
+        .last_key()
+        .as_ref()
+        .ok_or("View hasn't tracked a key press")?;
+
+    if let Key::Char(c) = *key {
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.input.push(c);
+        } else {
+            bail!("Can't push replacement character outside of replace mode");
+        }
+    } else {
+        bail!("Last key press wasn't a character")
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.input.pop();
+    } else {
+        bail!("Can't pop replacement character outside of replace mode");
+    }
+
+    Ok(())
+}
+
+pub fn accept_replacement(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.insert = false;
+    } else {
+        bail!("Can't accept replacement outside of replace mode");
+    }
+    commands::buffer::start_command_group(app)?;
+
+    let result = move_to_current_result(app);
+    abandon_on_error(app, result)
+}
+
+pub fn replace_current(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let result = mode.replace_current(buffer);
+        abandon_on_error(app, result)?;
+    } else {
+        bail!("Can't replace search result outside of replace mode");
+    }
+
+    advance(app)
+}
+
+pub fn skip(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.skip()?;
+    } else {
+        bail!("Can't skip search result outside of replace mode");
+    }
+
+    advance(app)
+}
+
+pub fn replace_all(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let mut result = Ok(());
+        while result.is_ok() && !mode.is_complete() {
+            result = mode.replace_current(buffer);
+        }
+        abandon_on_error(app, result)?;
+    } else {
+        bail!("Can't replace search results outside of replace mode");
+    }
+
+    advance(app)
+}
+
+fn advance(app: &mut Application) -> Result {
+    let complete = match app.mode {
+        Mode::Replace(ref mode) => mode.is_complete(),
+        _ => bail!("Can't advance to search result outside of replace mode"),
+    };
+
+    if complete {
+        commands::application::switch_to_normal_mode(app)?;
+        commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+    } else {
+        let result = move_to_current_result(app);
+        abandon_on_error(app, result)
+    }
+}
+
+// Leaves replace mode when something goes wrong, which
+// also ends the operation group started for it.
+fn abandon_on_error(app: &mut Application, result: Result) -> Result {
+    if result.is_err() {
+        let _ = commands::application::switch_to_normal_mode(app);
+    }
+
+    result
+}
+
+fn move_to_current_result(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mode) = app.mode {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let result = mode.current_result().ok_or(NO_SEARCH_RESULTS)?;
+        buffer.cursor.move_to(result.start());
+    } else {
+        bail!("Can't move to search result outside of replace mode");
+    }
+
+    commands::view::scroll_cursor_to_center(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::Mode;
+    use crate::models::Application;
+    use scribe::buffer::{Position, Range};
+    use scribe::Buffer;
+
+    fn replace_mode(app: &mut Application, content: &str, query: &str, replacement: &str) {
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        app.workspace.add_buffer(buffer);
+
+        app.search_query = Some(String::from(query));
+        commands::application::switch_to_search_mode(app).unwrap();
+        commands::search::accept_query(app).unwrap();
+        commands::application::switch_to_replace_mode(app).unwrap();
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.input = String::from(replacement);
+        }
+        commands::replace::accept_replacement(app).unwrap();
+    }
+
+    #[test]
+    fn switch_to_replace_mode_fails_without_search_results() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit");
+        app.workspace.add_buffer(buffer);
+
+        app.search_query = Some(String::from("missing"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        let _ = commands::search::accept_query(&mut app);
+
+        assert!(commands::application::switch_to_replace_mode(&mut app).is_err());
+        assert!(matches!(app.mode, Mode::Search(_)));
+    }
+
+    #[test]
+    fn replace_current_and_skip_confirm_each_match() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        replace_mode(&mut app, "one two one two one", "one", "three");
+
+        commands::replace::replace_current(&mut app).unwrap();
+        commands::replace::skip(&mut app).unwrap();
+        commands::replace::replace_current(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "three two three two one"
+        );
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
+    #[test]
+    fn replace_all_can_be_undone_in_a_single_step() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        replace_mode(&mut app, "one two one two one", "one", "three");
+
+        commands::replace::replace_all(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "three two three two three"
+        );
+        assert!(matches!(app.mode, Mode::Normal));
+
+        commands::buffer::undo(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "one two one two one"
+        );
+    }
+
+    #[test]
+    fn replace_all_expands_regex_capture_groups() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.search_regex = true;
+        replace_mode(&mut app, "let a = 1;\nlet b = 2;", r"let (\w)", "const $1");
+
+        commands::replace::replace_all(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "const a = 1;\nconst b = 2;"
+        );
+    }
+
+    #[test]
+    fn replace_current_leaves_replace_mode_when_a_result_no_longer_matches() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.search_regex = true;
+        replace_mode(&mut app, "one two one", "one", "three");
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        buffer.delete_range(Range::new(
+            Position { line: 0, offset: 0 },
+            Position { line: 0, offset: 1 },
+        ));
+
+        assert!(commands::replace::replace_current(&mut app).is_err());
+        assert!(matches!(app.mode, Mode::Normal));
+
+        // The group started for the replacements has ended,
+        // so undoing doesn't reach past the edit made above.
+        app.workspace.current_buffer.as_mut().unwrap().insert("x");
+        commands::buffer::undo(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "ne two one"
+        );
+    }
+}
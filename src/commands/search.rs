@@ -55,12 +55,37 @@ pub fn move_to_current_result(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn add_cursors_at_results(app: &mut Application) -> Result {
+    if let Mode::Search(ref mode) = app.mode {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        let results = mode.results.as_ref().ok_or(NO_SEARCH_RESULTS)?;
+        let current_result = results.selection().ok_or(NO_SEARCH_RESULTS)?;
+        buffer.cursor.move_to(current_result.start());
+
+        let cursors = results.iter().map(|result| result.start()).collect();
+        app.cursors.set(buffer, cursors);
+    } else {
+        bail!("Can't add cursors at search results outside of search mode");
+    }
+
+    commands::application::switch_to_normal_mode(app)
+}
+
 pub fn accept_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         mode.insert = false;
     } else {
         bail!("Can't accept search query outside of search mode");
     }
+    app.search_highlight = app.preferences.borrow().search_highlight();
+    if let Some(ref query) = app.search_query {
+        // Failing to save the history shouldn't stop the search.
+        let _ = app.search_history.push(query.as_str());
+    }
     run(app)?;
 
     Ok(())
@@ -77,6 +102,12 @@ pub fn clear_query(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn clear_highlight(app: &mut Application) -> Result {
+    app.search_highlight = false;
+
+    Ok(())
+}
+
 pub fn push_search_char(app: &mut Application) -> Result {
     let key = app
         .view
@@ -112,6 +143,55 @@ pub fn pop_search_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn previous_history_entry(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        let input = mode.input.clone().unwrap_or_default();
+        if let Some(entry) = app.search_history.previous(&input) {
+            mode.input = Some(entry.to_string());
+            app.search_query = mode.input.clone();
+        }
+    } else {
+        bail!("Can't recall search history outside of search mode");
+    }
+
+    Ok(())
+}
+
+pub fn next_history_entry(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        if let Some(entry) = app.search_history.next() {
+            mode.input = Some(entry.to_string());
+            app.search_query = mode.input.clone();
+        }
+    } else {
+        bail!("Can't recall search history outside of search mode");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_regex(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.regex = !mode.regex;
+        app.search_regex = mode.regex;
+    } else {
+        bail!("Can't toggle regex search outside of search mode");
+    };
+
+    Ok(())
+}
+
+pub fn toggle_case(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.case = mode.case.next();
+        app.preferences.borrow_mut().set_search_case(mode.case);
+    } else {
+        bail!("Can't toggle search case sensitivity outside of search mode");
+    };
+
+    Ok(())
+}
+
 pub fn run(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         let buffer = app
@@ -152,10 +232,14 @@ fn select_closest_result(app: &mut Application) -> Result {
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::modes::SearchCase;
     use crate::models::application::Mode;
+    use crate::models::application::Preferences;
     use crate::models::Application;
     use scribe::buffer::Position;
     use scribe::Buffer;
+    use yaml_rust::YamlLoader;
 
     #[test]
     fn move_to_previous_result_moves_cursor_to_previous_result() {
@@ -270,4 +354,138 @@ mod tests {
             Position { line: 1, offset: 0 }
         );
     }
+
+    #[test]
+    fn accept_query_enables_highlighting_when_preferred() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit");
+        app.workspace.add_buffer(buffer);
+        let data = YamlLoader::load_from_str("search:\n  highlight: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        app.preferences.replace(preferences);
+
+        app.search_query = Some(String::from("ed"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::accept_query(&mut app).unwrap();
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+
+        assert!(app.search_highlight);
+
+        commands::search::clear_highlight(&mut app).unwrap();
+
+        assert!(!app.search_highlight);
+    }
+
+    #[test]
+    fn history_entries_can_be_recalled_in_search_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit");
+        app.workspace.add_buffer(buffer);
+
+        app.search_query = Some(String::from("ed"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::accept_query(&mut app).unwrap();
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::clear_query(&mut app).unwrap();
+
+        commands::search::previous_history_entry(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("ed")));
+
+        commands::search::next_history_entry(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::new()));
+    }
+
+    #[test]
+    fn toggle_regex_updates_mode_and_application_state() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::toggle_regex(&mut app).unwrap();
+
+        assert!(match app.mode {
+            Mode::Search(ref mode) => mode.regex,
+            _ => false,
+        });
+        assert!(app.search_regex);
+
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+
+        assert!(match app.mode {
+            Mode::Search(ref mode) => mode.regex,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn accept_query_moves_to_regex_matches() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("first 12\nsecond 345");
+        app.workspace.add_buffer(buffer);
+
+        app.search_regex = true;
+        app.search_query = Some(String::from(r"\d{3}"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::accept_query(&mut app).unwrap();
+
+        assert_eq!(
+            *app.workspace.current_buffer.as_ref().unwrap().cursor,
+            Position { line: 1, offset: 7 }
+        );
+    }
+
+    #[test]
+    fn toggle_case_cycles_case_sensitivity_and_updates_preferences() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        app.preferences
+            .borrow_mut()
+            .set_search_case(SearchCase::Sensitive);
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::toggle_case(&mut app).unwrap();
+
+        assert!(match app.mode {
+            Mode::Search(ref mode) => mode.case == SearchCase::Insensitive,
+            _ => false,
+        });
+        assert_eq!(
+            app.preferences.borrow().search_case(),
+            SearchCase::Insensitive
+        );
+
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+
+        assert!(match app.mode {
+            Mode::Search(ref mode) => mode.case == SearchCase::Insensitive,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn add_cursors_at_results_edits_every_result() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\none");
+        app.workspace.add_buffer(buffer);
+
+        app.search_query = Some(String::from("one"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::accept_query(&mut app).unwrap();
+        commands::search::add_cursors_at_results(&mut app).unwrap();
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('_'));
+        commands::buffer::insert_char(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Insert));
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "_one\ntwo\n_one"
+        );
+    }
 }
@@ -4,7 +4,9 @@ use crate::input::Key;
 use crate::models::application::modes::open::DisplayablePath;
 use crate::models::application::modes::SearchSelectMode;
 use crate::models::application::{Application, Mode};
+use crate::util;
 use std::mem;
+use std::path::Path;
 
 pub fn accept(app: &mut Application) -> Result {
     let mut app_mode = mem::replace(&mut app.mode, Mode::Normal);
@@ -15,28 +17,24 @@ pub fn accept(app: &mut Application) -> Result {
 
             (selection.command)(app)?;
         }
-        Mode::Open(ref mut mode) => {
-            let DisplayablePath(path) = mode
+        Mode::Grep(ref mut mode) => {
+            let result = mode
                 .selection()
-                .ok_or("Couldn't find a selected path to open")?;
+                .ok_or("Couldn't find a selected result to open")?;
 
-            let syntax_definition = app
-                .preferences
-                .borrow()
-                .syntax_definition_name(path)
-                .and_then(|name| app.workspace.syntax_set.find_syntax_by_name(&name).cloned());
-
-            app.workspace
-                .open_buffer(path)
-                .chain_err(|| "Couldn't open a buffer for the specified path.")?;
+            open_buffer(app, &result.path)?;
 
             let buffer = app.workspace.current_buffer.as_mut().unwrap();
-
-            if syntax_definition.is_some() {
-                buffer.syntax_definition = syntax_definition;
+            if !buffer.cursor.move_to(result.position) {
+                bail!("Couldn't move to the selected result's position");
             }
+        }
+        Mode::Open(ref mut mode) => {
+            let DisplayablePath(path) = mode
+                .selection()
+                .ok_or("Couldn't find a selected path to open")?;
 
-            app.view.initialize_buffer(buffer)?;
+            open_buffer(app, path)?;
         }
         Mode::Theme(ref mut mode) => {
             let theme_key = mode.selection().ok_or("No theme selected")?;
@@ -67,6 +65,22 @@ pub fn accept(app: &mut Application) -> Result {
                 .ok_or(BUFFER_MISSING)?;
             buffer.syntax_definition = syntax;
         }
+        Mode::BufferList(ref mut mode) => {
+            let entry = mode.selection().ok_or("No buffer selected")?;
+
+            if !util::select_buffer(&mut app.workspace, entry.id) {
+                bail!("Couldn't find the selected buffer");
+            }
+        }
+        Mode::ClipboardHistory(ref mut mode) => {
+            let entry = mode.selection().ok_or("No clipboard entry selected")?;
+            commands::buffer::paste_content(app, Some(entry.content.clone()))?;
+        }
+        Mode::RecentFiles(ref mut mode) => {
+            let path = mode.selection().ok_or("No recent file selected")?;
+
+            open_buffer(app, Path::new(path))?;
+        }
         _ => bail!("Can't accept selection outside of search select mode."),
     }
 
@@ -75,13 +89,86 @@ pub fn accept(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Closes the buffer selected in buffer list mode, leaving the mode
+/// open to pick another. Buffers with unsaved changes are left alone.
+pub fn close_selected(app: &mut Application) -> Result {
+    let id = match app.mode {
+        Mode::BufferList(ref mode) => mode.selection().ok_or("No buffer selected")?.id,
+        _ => bail!("Can't close a buffer outside of buffer list mode"),
+    };
+    let current = app.workspace.current_buffer.as_ref().and_then(|b| b.id);
+
+    if !util::select_buffer(&mut app.workspace, id) {
+        bail!("Couldn't find the selected buffer");
+    }
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let closable = !buffer.modified() || buffer.data().is_empty();
+    if closable {
+        app.view.forget_buffer(buffer)?;
+        app.workspace.close_current_buffer();
+        app.recent_buffers.retain(|recent| *recent != id);
+    }
+    if let Some(current) = current.filter(|current| *current != id) {
+        util::select_buffer(&mut app.workspace, current);
+    }
+    if !closable {
+        bail!("The selected buffer has unsaved changes");
+    }
+
+    let empty = match app.mode {
+        Mode::BufferList(ref mut mode) => {
+            mode.remove(id);
+            mode.is_empty()
+        }
+        _ => false,
+    };
+    if empty {
+        application::switch_to_normal_mode(app)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn open_buffer(app: &mut Application, path: &Path) -> Result {
+    let syntax_definition = app
+        .preferences
+        .borrow()
+        .syntax_definition_name(path)
+        .and_then(|name| app.workspace.syntax_set.find_syntax_by_name(&name).cloned());
+
+    app.workspace
+        .open_buffer(path)
+        .chain_err(|| "Couldn't open a buffer for the specified path.")?;
+
+    let buffer = app.workspace.current_buffer.as_mut().unwrap();
+
+    if syntax_definition.is_some() {
+        buffer.syntax_definition = syntax_definition;
+    }
+
+    app.view.initialize_buffer(buffer)?;
+    if let Some(ref path) = buffer.path {
+        let _ = app.recent_files.push(path.to_string_lossy());
+    }
+
+    Ok(())
+}
+
 pub fn search(app: &mut Application) -> Result {
     match app.mode {
         Mode::Command(ref mut mode) => mode.search(),
+        Mode::Grep(ref mut mode) => mode.search(),
         Mode::Open(ref mut mode) => mode.search(),
         Mode::Theme(ref mut mode) => mode.search(),
         Mode::SymbolJump(ref mut mode) => mode.search(),
         Mode::Syntax(ref mut mode) => mode.search(),
+        Mode::BufferList(ref mut mode) => mode.search(),
+        Mode::ClipboardHistory(ref mut mode) => mode.search(),
+        Mode::RecentFiles(ref mut mode) => mode.search(),
         _ => bail!("Can't search outside of search select mode."),
     };
 
@@ -91,10 +178,14 @@ pub fn search(app: &mut Application) -> Result {
 pub fn select_next(app: &mut Application) -> Result {
     match app.mode {
         Mode::Command(ref mut mode) => mode.select_next(),
+        Mode::Grep(ref mut mode) => mode.select_next(),
         Mode::Open(ref mut mode) => mode.select_next(),
         Mode::Theme(ref mut mode) => mode.select_next(),
         Mode::SymbolJump(ref mut mode) => mode.select_next(),
         Mode::Syntax(ref mut mode) => mode.select_next(),
+        Mode::BufferList(ref mut mode) => mode.select_next(),
+        Mode::ClipboardHistory(ref mut mode) => mode.select_next(),
+        Mode::RecentFiles(ref mut mode) => mode.select_next(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
 
@@ -104,10 +195,14 @@ pub fn select_next(app: &mut Application) -> Result {
 pub fn select_previous(app: &mut Application) -> Result {
     match app.mode {
         Mode::Command(ref mut mode) => mode.select_previous(),
+        Mode::Grep(ref mut mode) => mode.select_previous(),
         Mode::Open(ref mut mode) => mode.select_previous(),
         Mode::Theme(ref mut mode) => mode.select_previous(),
         Mode::SymbolJump(ref mut mode) => mode.select_previous(),
         Mode::Syntax(ref mut mode) => mode.select_previous(),
+        Mode::BufferList(ref mut mode) => mode.select_previous(),
+        Mode::ClipboardHistory(ref mut mode) => mode.select_previous(),
+        Mode::RecentFiles(ref mut mode) => mode.select_previous(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
 
@@ -117,10 +212,14 @@ pub fn select_previous(app: &mut Application) -> Result {
 pub fn enable_insert(app: &mut Application) -> Result {
     match app.mode {
         Mode::Command(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Grep(ref mut mode) => mode.set_insert_mode(true),
         Mode::Open(ref mut mode) => mode.set_insert_mode(true),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(true),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(true),
         Mode::Syntax(ref mut mode) => mode.set_insert_mode(true),
+        Mode::BufferList(ref mut mode) => mode.set_insert_mode(true),
+        Mode::ClipboardHistory(ref mut mode) => mode.set_insert_mode(true),
+        Mode::RecentFiles(ref mut mode) => mode.set_insert_mode(true),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
 
@@ -130,10 +229,14 @@ pub fn enable_insert(app: &mut Application) -> Result {
 pub fn disable_insert(app: &mut Application) -> Result {
     match app.mode {
         Mode::Command(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Grep(ref mut mode) => mode.set_insert_mode(false),
         Mode::Open(ref mut mode) => mode.set_insert_mode(false),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(false),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(false),
         Mode::Syntax(ref mut mode) => mode.set_insert_mode(false),
+        Mode::BufferList(ref mut mode) => mode.set_insert_mode(false),
+        Mode::ClipboardHistory(ref mut mode) => mode.set_insert_mode(false),
+        Mode::RecentFiles(ref mut mode) => mode.set_insert_mode(false),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
 
@@ -144,10 +247,14 @@ pub fn push_search_char(app: &mut Application) -> Result {
     if let Some(Key::Char(c)) = *app.view.last_key() {
         match app.mode {
             Mode::Command(ref mut mode) => mode.push_search_char(c),
+            Mode::Grep(ref mut mode) => mode.push_search_char(c),
             Mode::Open(ref mut mode) => mode.push_search_char(c),
             Mode::Theme(ref mut mode) => mode.push_search_char(c),
             Mode::SymbolJump(ref mut mode) => mode.push_search_char(c),
             Mode::Syntax(ref mut mode) => mode.push_search_char(c),
+            Mode::BufferList(ref mut mode) => mode.push_search_char(c),
+            Mode::ClipboardHistory(ref mut mode) => mode.push_search_char(c),
+            Mode::RecentFiles(ref mut mode) => mode.push_search_char(c),
             _ => bail!("Can't push search character outside of search select mode"),
         }
     }
@@ -158,10 +265,14 @@ pub fn push_search_char(app: &mut Application) -> Result {
 pub fn pop_search_token(app: &mut Application) -> Result {
     match app.mode {
         Mode::Command(ref mut mode) => mode.pop_search_token(),
+        Mode::Grep(ref mut mode) => mode.pop_search_token(),
         Mode::Open(ref mut mode) => mode.pop_search_token(),
         Mode::Theme(ref mut mode) => mode.pop_search_token(),
         Mode::SymbolJump(ref mut mode) => mode.pop_search_token(),
         Mode::Syntax(ref mut mode) => mode.pop_search_token(),
+        Mode::BufferList(ref mut mode) => mode.pop_search_token(),
+        Mode::ClipboardHistory(ref mut mode) => mode.pop_search_token(),
+        Mode::RecentFiles(ref mut mode) => mode.pop_search_token(),
         _ => bail!("Can't pop search token outside of search select mode"),
     }
 
@@ -172,10 +283,14 @@ pub fn pop_search_token(app: &mut Application) -> Result {
 pub fn step_back(app: &mut Application) -> Result {
     let result_count = match app.mode {
         Mode::Command(ref mut mode) => mode.results().count(),
+        Mode::Grep(ref mut mode) => mode.results().count(),
         Mode::Open(ref mut mode) => mode.results().count(),
         Mode::Theme(ref mut mode) => mode.results().count(),
         Mode::SymbolJump(ref mut mode) => mode.results().count(),
         Mode::Syntax(ref mut mode) => mode.results().count(),
+        Mode::BufferList(ref mut mode) => mode.results().count(),
+        Mode::ClipboardHistory(ref mut mode) => mode.results().count(),
+        Mode::RecentFiles(ref mut mode) => mode.results().count(),
         _ => bail!("Can't pop search token outside of search select mode"),
     };
 
@@ -1,18 +1,28 @@
 use super::application;
 use crate::commands::{self, Result};
 use crate::errors::*;
+use crate::input::Key;
+use crate::models::application::modes::{SearchCase, SearchMode, SelectMode};
 use crate::models::application::{Application, ClipboardContent, Mode};
 use crate::util;
 use crate::util::reflow::Reflow;
-use scribe::buffer::{LineRange, Range};
+use crate::util::text_object::{self, TextObject};
+use scribe::buffer::{LineRange, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn delete(app: &mut Application) -> Result {
-    let rng = sel_to_range(app)?;
-    let buf = app.workspace.current_buffer.as_mut().unwrap();
-    buf.delete_range(rng.clone());
-    buf.cursor.move_to(rng.start());
+    if let Mode::SelectBlock(_) = app.mode {
+        return delete_block(app);
+    }
 
-    Ok(())
+    util::at_each_cursor(app, |app| {
+        let rng = sel_to_range(app)?;
+        let buf = app.workspace.current_buffer.as_mut().unwrap();
+        buf.delete_range(rng.clone());
+        buf.cursor.move_to(rng.start());
+
+        Ok(())
+    })
 }
 
 pub fn copy_and_delete(app: &mut Application) -> Result {
@@ -22,11 +32,27 @@ pub fn copy_and_delete(app: &mut Application) -> Result {
 
 pub fn change(app: &mut Application) -> Result {
     let _ = copy_to_clipboard(app);
+    if let Mode::SelectBlock(_) = app.mode {
+        let ranges = block_ranges(app)?;
+        delete_block(app)?;
+        return insert_at_block(app, ranges.iter().map(|range| range.start()).collect());
+    }
+
     delete(app)?;
     application::switch_to_insert_mode(app)?;
     commands::view::scroll_to_cursor(app)
 }
 
+pub fn insert_before_block(app: &mut Application) -> Result {
+    let ranges = block_ranges(app)?;
+    insert_at_block(app, ranges.iter().map(|range| range.start()).collect())
+}
+
+pub fn append_after_block(app: &mut Application) -> Result {
+    let ranges = block_ranges(app)?;
+    insert_at_block(app, ranges.iter().map(|range| range.end()).collect())
+}
+
 pub fn copy(app: &mut Application) -> Result {
     copy_to_clipboard(app)?;
     application::switch_to_normal_mode(app)
@@ -65,7 +91,253 @@ pub fn justify(app: &mut Application) -> Result {
     application::switch_to_normal_mode(app)
 }
 
+pub fn add_cursor_at_next_occurrence(app: &mut Application) -> Result {
+    let range = match app.mode {
+        Mode::Select(_) => sel_to_range(app)?,
+        _ => bail!("Can't add a cursor outside of select mode"),
+    };
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let content = buffer
+        .read(&range)
+        .ok_or("Couldn't read selected data from buffer")?;
+    if content.is_empty() {
+        bail!("A selection is required.");
+    }
+
+    let mut search = SearchMode::new(Some(content), false, SearchCase::Sensitive);
+    search.search(buffer)?;
+
+    // Cursors are placed on the same side of each occurrence as the
+    // primary cursor is on the selection, which they select in turn.
+    let cursor = *buffer.cursor.clone();
+    let at_end = cursor == range.end();
+    let cursors = app.cursors.positions(buffer);
+    let last_cursor = cursors
+        .last()
+        .filter(|last| **last > cursor)
+        .copied()
+        .unwrap_or(cursor);
+    let targets: Vec<(Position, Position)> = search
+        .results
+        .iter()
+        .flat_map(|results| results.iter())
+        .map(|result| {
+            if at_end {
+                (result.end(), result.start())
+            } else {
+                (result.start(), result.end())
+            }
+        })
+        .filter(|(target, _)| *target != cursor && !cursors.contains(target))
+        .collect();
+    let (target, anchor) = targets
+        .iter()
+        .find(|(target, _)| *target > last_cursor)
+        .or_else(|| targets.first())
+        .copied()
+        .ok_or("No more occurrences of the selection")?;
+
+    app.cursors.add_selection(buffer, target, Some(anchor));
+
+    Ok(())
+}
+
+pub fn add_cursors_to_lines(app: &mut Application) -> Result {
+    let line_range = match app.mode {
+        Mode::SelectLine(ref mode) => {
+            let buffer = app
+                .workspace
+                .current_buffer
+                .as_ref()
+                .ok_or(BUFFER_MISSING)?;
+            LineRange::new(mode.anchor, buffer.cursor.line)
+        }
+        _ => bail!("Can't add cursors to lines outside of select line mode"),
+    };
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let offset = buffer.cursor.offset;
+    let data = buffer.data();
+    let cursors = data
+        .lines()
+        .enumerate()
+        .skip(line_range.start())
+        .take(line_range.end() - line_range.start() + 1)
+        .map(|(line, content)| Position {
+            line,
+            offset: offset.min(content.graphemes(true).count()),
+        })
+        .collect();
+    app.cursors.set(buffer, cursors);
+
+    application::switch_to_normal_mode(app)
+}
+
+pub fn play_macro_on_lines(app: &mut Application) -> Result {
+    let line_range = match app.mode {
+        Mode::SelectLine(ref mode) => {
+            let buffer = app
+                .workspace
+                .current_buffer
+                .as_ref()
+                .ok_or(BUFFER_MISSING)?;
+            LineRange::new(mode.anchor, buffer.cursor.line)
+        }
+        _ => bail!("Can't play a macro on lines outside of select line mode"),
+    };
+    application::switch_to_normal_mode(app)?;
+    if app.workspace.current_buffer.is_none() {
+        bail!(BUFFER_MISSING);
+    }
+
+    // Working from the bottom up leaves the lines still to be
+    // visited in place, even if the macro adds or removes lines.
+    // The group is held so that macros using insert mode don't end it.
+    app.hold_operation_group();
+    let mut result = Ok(());
+    for line in (line_range.start()..=line_range.end()).rev() {
+        if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+            buffer.cursor.move_to(Position { line, offset: 0 });
+        }
+
+        result = application::play_macro(app);
+        if result.is_err() {
+            break;
+        }
+        if !matches!(app.mode, Mode::Normal) {
+            let _ = application::switch_to_normal_mode(app);
+        }
+    }
+    app.release_operation_group();
+    result?;
+
+    commands::view::scroll_to_cursor(app)
+}
+
+pub fn select_inner_object(app: &mut Application) -> Result {
+    select_object(app, false)
+}
+
+pub fn select_around_object(app: &mut Application) -> Result {
+    select_object(app, true)
+}
+
+// Places a cursor at each of the positions (one per block line)
+// and switches to insert mode, so that typing edits every line.
+fn insert_at_block(app: &mut Application, positions: Vec<Position>) -> Result {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?;
+    let first_position = *positions.first().ok_or("A selection is required.")?;
+    buffer.cursor.move_to(first_position);
+    app.cursors.set(buffer, positions);
+
+    application::switch_to_insert_mode(app)
+}
+
+// Replaces the selection with the text object named by the last key.
+fn select_object(app: &mut Application, around: bool) -> Result {
+    let object = match app.view.last_key() {
+        Some(Key::Char(c)) => TextObject::from_char(*c),
+        _ => None,
+    }
+    .ok_or("Unknown text object")?;
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let range = match object {
+        TextObject::Syntax(kind) => {
+            let tokens = app
+                .workspace
+                .current_buffer_tokens()
+                .chain_err(|| BUFFER_TOKENS_FAILED)?;
+            let token_iter = tokens.iter().chain_err(|| BUFFER_PARSE_FAILED)?;
+
+            text_object::find_syntax_object(token_iter, &data, &buffer.cursor, kind, around)
+        }
+        _ => text_object::find(&data, &buffer.cursor, object, around),
+    }
+    .ok_or("No text object found at the cursor")?;
+
+    app.mode = Mode::Select(SelectMode::new(range.start()));
+    app.workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?
+        .cursor
+        .move_to(range.end());
+
+    commands::view::scroll_to_cursor(app)
+}
+
+fn delete_block(app: &mut Application) -> Result {
+    let ranges = block_ranges(app)?;
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?;
+
+    buffer.start_operation_group();
+    for range in ranges.iter().rev() {
+        buffer.delete_range(range.clone());
+    }
+    buffer.end_operation_group();
+    if let Some(range) = ranges.first() {
+        buffer.cursor.move_to(range.start());
+    }
+
+    Ok(())
+}
+
+fn copy_block_to_clipboard(app: &mut Application) -> Result {
+    let ranges = block_ranges(app)?;
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let rows: Vec<String> = ranges
+        .iter()
+        .map(|range| buffer.read(range).unwrap_or_default())
+        .collect();
+
+    app.clipboard
+        .set_content(ClipboardContent::Rectangle(rows.join("\n")))
+}
+
+fn block_ranges(app: &Application) -> std::result::Result<Vec<Range>, Error> {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+
+    match app.mode {
+        Mode::SelectBlock(ref mode) => {
+            Ok(mode.to_ranges(&buffer.data(), &buffer.cursor, app.tab_width()))
+        }
+        _ => bail!("A block selection is required."),
+    }
+}
+
 fn copy_to_clipboard(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(_) = app.mode {
+        return copy_block_to_clipboard(app);
+    }
+
     let buffer = app
         .workspace
         .current_buffer
@@ -97,7 +369,7 @@ fn copy_to_clipboard(app: &mut Application) -> Result {
     Ok(())
 }
 
-fn sel_to_range(app: &mut Application) -> std::result::Result<Range, Error> {
+pub(crate) fn sel_to_range(app: &mut Application) -> std::result::Result<Range, Error> {
     let buf = app
         .workspace
         .current_buffer
@@ -126,6 +398,7 @@ fn sel_to_range(app: &mut Application) -> std::result::Result<Range, Error> {
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
     use crate::models::application::{Application, Mode};
     use scribe::buffer::Position;
     use scribe::Buffer;
@@ -214,4 +487,139 @@ mod tests {
             String::from("nexedit")
         )
     }
+
+    #[test]
+    fn add_cursor_at_next_occurrence_adds_cursors_in_order_and_wraps() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one two one two one");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        commands::cursor::move_to_end_of_current_token(&mut app).unwrap();
+        super::add_cursor_at_next_occurrence(&mut app).unwrap();
+        super::add_cursor_at_next_occurrence(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(
+            app.cursors.positions(buffer),
+            &[
+                Position { line: 0, offset: 11 },
+                Position { line: 0, offset: 19 },
+            ]
+        );
+        assert!(super::add_cursor_at_next_occurrence(&mut app).is_err());
+    }
+
+    #[test]
+    fn change_replaces_the_selection_at_every_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one two one two one");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        commands::cursor::move_to_end_of_current_token(&mut app).unwrap();
+        super::add_cursor_at_next_occurrence(&mut app).unwrap();
+        super::add_cursor_at_next_occurrence(&mut app).unwrap();
+        super::change(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('1'));
+        commands::buffer::insert_char(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "1 two 1 two 1"
+        );
+    }
+
+    #[test]
+    fn add_cursors_to_lines_adds_a_cursor_to_each_selected_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\na\nnexedit");
+        buffer.cursor.move_to(Position { line: 0, offset: 4 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        super::add_cursors_to_lines(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(
+            app.cursors.positions(buffer),
+            &[
+                Position { line: 0, offset: 4 },
+                Position { line: 1, offset: 1 },
+            ]
+        );
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
+    #[test]
+    fn delete_removes_a_block_selection_in_a_single_step() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\nnexedit\nnexedit");
+        buffer.cursor.move_to(Position { line: 0, offset: 1 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::cursor::move_right(&mut app).unwrap();
+        commands::cursor::move_right(&mut app).unwrap();
+        super::delete(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "nedit\nnedit\nnexedit"
+        );
+
+        commands::buffer::undo(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "nexedit\nnexedit\nnexedit"
+        );
+    }
+
+    #[test]
+    fn insert_before_block_inserts_on_every_line_of_the_block() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\nnexedit\nnexedit");
+        buffer.cursor.move_to(Position { line: 0, offset: 3 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        super::insert_before_block(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('-'));
+        commands::buffer::insert_char(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Insert));
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "nex-edit\nnex-edit\nnex-edit"
+        );
+    }
+
+    #[test]
+    fn select_inner_object_selects_the_text_object_at_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("call(one, two)");
+        buffer.cursor.move_to(Position { line: 0, offset: 7 });
+        app.workspace.add_buffer(buffer);
+
+        app.view.last_key = Some(Key::Char('('));
+        super::select_inner_object(&mut app).unwrap();
+        super::delete(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer.as_ref().unwrap().data(),
+            "call()"
+        );
+    }
 }
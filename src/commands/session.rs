@@ -0,0 +1,227 @@
+use crate::commands::{self, Result};
+use crate::errors::*;
+use crate::models::application::{Application, Session, SessionBuffer};
+use crate::util;
+use std::path::Path;
+
+/// Records the open buffers, their cursors and scroll offsets, the search
+/// query, and any theme or syntaxes picked by the user, to be restored the
+/// next time the workspace is opened. Buffers that haven't been saved to a
+/// file are left out.
+pub fn save(app: &mut Application) -> Result {
+    let path = app
+        .session_path
+        .clone()
+        .ok_or("Couldn't find a place to save the session")?;
+    let mut session = Session {
+        search_query: app.search_query.clone(),
+        theme: app.preferences.borrow().theme_override().map(String::from),
+        ..Default::default()
+    };
+
+    // Visit every buffer, returning to the current one.
+    let start = app.workspace.current_buffer.as_ref().and_then(|buf| buf.id);
+    while let Some(buf) = app.workspace.current_buffer.as_ref() {
+        if let Some(ref path) = buf.path {
+            if buf.id == start {
+                session.current = Some(session.buffers.len());
+            }
+            let detected = detected_syntax(app, path);
+            session.buffers.push(SessionBuffer {
+                path: path.clone(),
+                cursor: *buf.cursor,
+                scroll_offset: app.view.scroll_offset(buf)?,
+                syntax: buf
+                    .syntax_definition
+                    .as_ref()
+                    .map(|syntax| syntax.name.clone())
+                    .filter(|name| *name != detected),
+            });
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer.as_ref().and_then(|buf| buf.id) == start {
+            break;
+        }
+    }
+
+    session.save(&path)
+}
+
+// The syntax a buffer is given when it's opened, which
+// doesn't need to be recorded as an override.
+fn detected_syntax(app: &Application, path: &Path) -> String {
+    let syntax_set = &app.workspace.syntax_set;
+    let preferred = app.preferences.borrow().syntax_definition_name(path);
+
+    preferred
+        .and_then(|name| syntax_set.find_syntax_by_name(&name))
+        .or_else(|| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(|extension| syntax_set.find_syntax_by_extension(extension))
+        })
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+        .name
+        .clone()
+}
+
+/// Reopens the buffers recorded in the workspace's session, alongside
+/// any that are already open. Files that have since been removed or can no
+/// longer be opened are skipped.
+pub fn restore(app: &mut Application) -> Result {
+    let path = app
+        .session_path
+        .clone()
+        .ok_or("Couldn't find a saved session")?;
+    if !path.exists() {
+        bail!("No session has been saved for this workspace");
+    }
+    let session = Session::load(&path)?;
+
+    let mut current = None;
+    for (index, entry) in session.buffers.iter().enumerate() {
+        if !entry.path.exists() || commands::search_select::open_buffer(app, &entry.path).is_err() {
+            continue;
+        }
+
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_mut()
+            .ok_or(BUFFER_MISSING)?;
+        if let Some(ref name) = entry.syntax {
+            if let Some(syntax) = app.workspace.syntax_set.find_syntax_by_name(name) {
+                buffer.syntax_definition = Some(syntax.clone());
+            }
+        }
+        buffer.cursor.move_to(entry.cursor);
+        app.view.set_scroll_offset(buffer, entry.scroll_offset)?;
+
+        if session.current == Some(index) {
+            current = buffer.id;
+        }
+    }
+    if let Some(id) = current {
+        util::select_buffer(&mut app.workspace, id);
+    }
+
+    if session.search_query.is_some() {
+        app.search_query = session.search_query;
+    }
+    if let Some(theme) = session.theme {
+        app.preferences.borrow_mut().set_theme(theme);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Session, SessionBuffer};
+    use scribe::buffer::Position;
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+
+    #[test]
+    fn restore_reopens_the_saved_buffers_where_they_were_left() {
+        let session_path =
+            env::temp_dir().join(format!("nexedit_restored_session_{}.yml", process::id()));
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.session_path = Some(session_path.clone());
+
+        for path in ["Cargo.toml", "Cargo.lock"] {
+            commands::search_select::open_buffer(&mut app, Path::new(path)).unwrap();
+        }
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        buffer.cursor.move_to(Position { line: 4, offset: 2 });
+        app.view.set_scroll_offset(buffer, 1).unwrap();
+        app.workspace.next_buffer();
+        app.search_query = Some(String::from("nexedit"));
+        app.preferences.borrow_mut().set_theme("solarized_light");
+        super::save(&mut app).unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.session_path = Some(session_path.clone());
+        let result = super::restore(&mut app);
+        fs::remove_file(&session_path).unwrap();
+        result.unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer_path(),
+            Some(Path::new("Cargo.toml"))
+        );
+        assert_eq!(app.search_query, Some(String::from("nexedit")));
+        assert_eq!(app.preferences.borrow().theme(), "solarized_light");
+
+        app.workspace.next_buffer();
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        assert_eq!(
+            app.workspace.current_buffer_path(),
+            Some(Path::new("Cargo.lock"))
+        );
+        assert_eq!(*buffer.cursor, Position { line: 4, offset: 2 });
+        assert_eq!(app.view.scroll_offset(buffer).unwrap(), 1);
+    }
+
+    #[test]
+    fn restore_skips_buffers_that_cant_be_opened() {
+        let session_path =
+            env::temp_dir().join(format!("nexedit_unopenable_session_{}.yml", process::id()));
+        let buffer = |path: &str| SessionBuffer {
+            path: PathBuf::from(path),
+            cursor: Position::default(),
+            scroll_offset: 0,
+            syntax: None,
+        };
+        let session = Session {
+            buffers: vec![buffer("src"), buffer("Cargo.toml")],
+            ..Default::default()
+        };
+        session.save(&session_path).unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.session_path = Some(session_path.clone());
+        let result = super::restore(&mut app);
+        fs::remove_file(&session_path).unwrap();
+        result.unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer_path(),
+            Some(Path::new("Cargo.toml"))
+        );
+    }
+
+    #[test]
+    fn save_only_records_themes_and_syntaxes_picked_by_the_user() {
+        let session_path =
+            env::temp_dir().join(format!("nexedit_override_session_{}.yml", process::id()));
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.session_path = Some(session_path.clone());
+
+        for path in ["Cargo.toml", "src/main.rs"] {
+            commands::search_select::open_buffer(&mut app, Path::new(path)).unwrap();
+        }
+        let syntax = app.workspace.syntax_set.find_syntax_by_name("Markdown");
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .syntax_definition = syntax.cloned();
+        super::save(&mut app).unwrap();
+        let session = Session::load(&session_path);
+        fs::remove_file(&session_path).unwrap();
+        let session = session.unwrap();
+
+        assert_eq!(session.theme, None);
+        let syntaxes: Vec<_> = session
+            .buffers
+            .iter()
+            .map(|buf| buf.syntax.as_deref())
+            .collect();
+        assert_eq!(syntaxes, vec![Some("Markdown"), None]);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::commands::{self, application, Result};
+use crate::errors::*;
+use crate::input::Key;
+use crate::models::application::modes::{SurroundMode, SurroundTarget};
+use crate::models::application::{Application, Mode};
+use crate::util::surround::{self, Surrounding};
+use scribe::buffer::{Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+const TAG_KEY: char = 't';
+
+pub fn add(app: &mut Application) -> Result {
+    let mut range = commands::selection::sel_to_range(app)?;
+
+    // Line selections include the last line's newline, which shouldn't be wrapped.
+    if let Mode::SelectLine(_) = app.mode {
+        let buffer = app
+            .workspace
+            .current_buffer
+            .as_ref()
+            .ok_or(BUFFER_MISSING)?;
+        let end = range.end();
+        if end.offset == 0 && end.line > range.start().line {
+            let line = buffer
+                .data()
+                .lines()
+                .nth(end.line - 1)
+                .unwrap_or("")
+                .to_string();
+            range = Range::new(
+                range.start(),
+                Position {
+                    line: end.line - 1,
+                    offset: line.graphemes(true).count(),
+                },
+            );
+        }
+    }
+
+    if last_char(app)? == TAG_KEY {
+        app.mode = Mode::Surround(SurroundMode::new(SurroundTarget::Selection(range)));
+        return Ok(());
+    }
+    let pair = pair(app)?;
+    wrap(app, range, pair)?;
+
+    application::switch_to_normal_mode(app)
+}
+
+pub fn change(app: &mut Application) -> Result {
+    let surrounding = find_surrounding(app)?;
+
+    if last_char(app)? == TAG_KEY {
+        app.mode = Mode::Surround(SurroundMode::new(SurroundTarget::Surrounding(surrounding)));
+        return Ok(());
+    }
+    let pair = pair(app)?;
+
+    replace(app, surrounding, pair)
+}
+
+pub fn delete(app: &mut Application) -> Result {
+    let surrounding = find_surrounding(app)?;
+
+    replace(app, surrounding, (String::new(), String::new()))
+}
+
+pub fn push_char(app: &mut Application) -> Result {
+    let c = last_char(app)?;
+    if let Mode::Surround(ref mut mode) = app.mode {
+        mode.input.push(c);
+    } else {
+        bail!("Can't push a tag character outside of surround mode");
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::Surround(ref mut mode) = app.mode {
+        mode.input.pop();
+    } else {
+        bail!("Can't pop a tag character outside of surround mode");
+    }
+
+    Ok(())
+}
+
+pub fn accept(app: &mut Application) -> Result {
+    let mode = match std::mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::Surround(mode) => mode,
+        _ => bail!("Can't accept a tag outside of surround mode"),
+    };
+    let pair = surround::tag_pair(&mode.input).ok_or("A tag name is required")?;
+
+    match mode.target {
+        SurroundTarget::Selection(range) => wrap(app, range, pair)?,
+        SurroundTarget::Surrounding(surrounding) => replace(app, surrounding, pair)?,
+    }
+
+    application::switch_to_normal_mode(app)
+}
+
+fn last_char(app: &Application) -> std::result::Result<char, Error> {
+    match app.view.last_key() {
+        Some(Key::Char(c)) => Ok(*c),
+        _ => bail!("Last key press wasn't a character"),
+    }
+}
+
+// The configured pair with an opening or closing
+// delimiter matching the last key pressed.
+fn pair(app: &Application) -> std::result::Result<(String, String), Error> {
+    let c = last_char(app)?;
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let pairs = app
+        .preferences
+        .borrow()
+        .surround_pairs(buffer.path.as_ref());
+
+    surround::pair_for(c, &pairs).ok_or_else(|| format!("No surround pair for \"{}\"", c).into())
+}
+
+fn find_surrounding(app: &Application) -> std::result::Result<Surrounding, Error> {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .ok_or(BUFFER_MISSING)?;
+    let pairs = app
+        .preferences
+        .borrow()
+        .surround_pairs(buffer.path.as_ref());
+
+    Ok(surround::find(&buffer.data(), &buffer.cursor, &pairs)
+        .ok_or("No surrounding pair found at the cursor")?)
+}
+
+fn wrap(app: &mut Application, range: Range, (open, close): (String, String)) -> Result {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?;
+
+    // Insert the closing delimiter first, so that
+    // the range's start remains in place.
+    buffer.start_operation_group();
+    buffer.cursor.move_to(range.end());
+    buffer.insert(close);
+    buffer.cursor.move_to(range.start());
+    buffer.insert(open);
+    buffer.end_operation_group();
+
+    commands::view::scroll_to_cursor(app)
+}
+
+fn replace(
+    app: &mut Application,
+    surrounding: Surrounding,
+    (open, close): (String, String),
+) -> Result {
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?;
+
+    buffer.start_operation_group();
+    buffer.delete_range(surrounding.close.clone());
+    buffer.cursor.move_to(surrounding.close.start());
+    buffer.insert(close);
+    buffer.delete_range(surrounding.open.clone());
+    buffer.cursor.move_to(surrounding.open.start());
+    buffer.insert(open);
+    buffer.end_operation_group();
+
+    commands::view::scroll_to_cursor(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use scribe::buffer::Position;
+    use scribe::Buffer;
+
+    fn app_with_buffer(data: &str, position: Position) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(data);
+        buffer.cursor.move_to(position);
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    fn data(app: &Application) -> String {
+        app.workspace.current_buffer.as_ref().unwrap().data()
+    }
+
+    #[test]
+    fn add_wraps_the_selection_in_the_pair_for_the_last_key() {
+        let mut app = app_with_buffer("nexedit", Position { line: 0, offset: 0 });
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        commands::cursor::move_to_end_of_line(&mut app).unwrap();
+
+        app.view.last_key = Some(Key::Char(')'));
+        super::add(&mut app).unwrap();
+
+        assert_eq!(data(&app), "(nexedit)");
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
+    #[test]
+    fn add_wraps_the_selection_in_an_entered_tag() {
+        let mut app = app_with_buffer("nexedit", Position { line: 0, offset: 0 });
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        commands::cursor::move_to_end_of_line(&mut app).unwrap();
+
+        app.view.last_key = Some(Key::Char('t'));
+        super::add(&mut app).unwrap();
+        for c in "a href=\"/\"".chars() {
+            app.view.last_key = Some(Key::Char(c));
+            super::push_char(&mut app).unwrap();
+        }
+        super::accept(&mut app).unwrap();
+
+        assert_eq!(data(&app), "<a href=\"/\">nexedit</a>");
+    }
+
+    #[test]
+    fn change_replaces_the_nearest_surrounding_pair() {
+        let mut app = app_with_buffer("call(\"nexedit\")", Position { line: 0, offset: 7 });
+
+        app.view.last_key = Some(Key::Char('\''));
+        super::change(&mut app).unwrap();
+
+        assert_eq!(data(&app), "call('nexedit')");
+    }
+
+    #[test]
+    fn delete_removes_the_nearest_surrounding_tags() {
+        let mut app = app_with_buffer("<p><b>nexedit</b></p>", Position { line: 0, offset: 8 });
+
+        super::delete(&mut app).unwrap();
+
+        assert_eq!(data(&app), "<p>nexedit</p>");
+    }
+}
@@ -5,41 +5,73 @@ use smallvec::SmallVec;
 use std::collections::HashMap;
 use std::convert::Into;
 use std::ops::{Deref, DerefMut};
+use std::slice;
 use yaml_rust::yaml::{Hash, Yaml, YamlLoader};
 
-pub struct KeyMap(HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>>);
+type KeyBindings = HashMap<Vec<Key>, SmallVec<[Command; 4]>>;
+
+// Keys bound to macros defined in the preferences, by name.
+type MacroBindings = HashMap<Vec<Key>, String>;
+
+const MACRO_PREFIX: &str = "macros::";
+
+pub struct KeyMap {
+    bindings: HashMap<String, KeyBindings>,
+    macros: HashMap<String, MacroBindings>,
+}
 
 impl KeyMap {
     pub fn from(keymap_data: &Hash) -> Result<KeyMap> {
         let mut keymap = HashMap::new();
+        let mut macros = HashMap::new();
         let commands = commands::hash_map();
 
         for (yaml_mode, yaml_key_bindings) in keymap_data {
             let mode = yaml_mode
                 .as_str()
                 .ok_or_else(|| "A mode key couldn't be parsed as a string".to_string())?;
-            let key_bindings = parse_mode_key_bindings(yaml_key_bindings, &commands)
-                .chain_err(|| format!("Failed to parse keymaps for \"{}\" mode", mode))?;
+            let (key_bindings, macro_bindings) =
+                parse_mode_key_bindings(yaml_key_bindings, &commands)
+                    .chain_err(|| format!("Failed to parse keymaps for \"{}\" mode", mode))?;
 
             keymap.insert(mode.to_string(), key_bindings);
+            macros.insert(mode.to_string(), macro_bindings);
         }
 
-        Ok(KeyMap(keymap))
+        Ok(KeyMap {
+            bindings: keymap,
+            macros,
+        })
     }
 
     pub fn commands_for(&self, mode: &str, key: &Key) -> Option<SmallVec<[Command; 4]>> {
-        self.0
-            .get(mode)
-            .and_then(|mode_keymap| {
-                if let Key::Char(_) = *key {
-                    mode_keymap
-                        .get(key)
-                        .or_else(|| mode_keymap.get(&Key::AnyChar))
-                } else {
-                    mode_keymap.get(key)
-                }
-            })
-            .map(|commands| (*commands).clone())
+        self.sequence_commands_for(mode, slice::from_ref(key))
+    }
+
+    /// Looks up the commands bound to a sequence of keys, falling back to
+    /// a wildcard binding when the final key is a character.
+    pub fn sequence_commands_for(
+        &self,
+        mode: &str,
+        keys: &[Key],
+    ) -> Option<SmallVec<[Command; 4]>> {
+        find_binding(self.bindings.get(mode)?, keys)
+    }
+
+    /// Looks up the name of the macro bound to a sequence of keys.
+    pub fn sequence_macro_for(&self, mode: &str, keys: &[Key]) -> Option<String> {
+        find_binding(self.macros.get(mode)?, keys)
+    }
+
+    /// Whether the keys are the start of a longer binding,
+    /// and should be held until the sequence is complete.
+    pub fn is_prefix(&self, mode: &str, keys: &[Key]) -> bool {
+        let command_sequences = self.bindings.get(mode).into_iter().flat_map(|b| b.keys());
+        let macro_sequences = self.macros.get(mode).into_iter().flat_map(|b| b.keys());
+
+        command_sequences
+            .chain(macro_sequences)
+            .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
     }
 
     pub fn default() -> Result<KeyMap> {
@@ -56,28 +88,62 @@ impl KeyMap {
         include_str!("default.yml")
     }
 
-    pub fn merge(&mut self, mut key_map: KeyMap) {
-        for (mode, other_key_bindings) in key_map.iter_mut() {
-            if let Some(key_bindings) = self.get_mut(mode) {
-                for (key, command) in other_key_bindings.drain() {
+    pub fn merge(&mut self, key_map: KeyMap) {
+        let KeyMap { bindings, macros } = key_map;
+
+        // Keys bound to a command in one keymap and a macro in
+        // the other end up bound to whichever was merged in.
+        for (mode, other_key_bindings) in bindings {
+            if let Some(key_bindings) = self.bindings.get_mut(&mode) {
+                let macro_bindings = self.macros.entry(mode).or_default();
+
+                for (key, command) in other_key_bindings {
+                    macro_bindings.remove(&key);
                     key_bindings.insert(key, command);
                 }
             }
         }
+        for (mode, other_macro_bindings) in macros {
+            if let Some(key_bindings) = self.bindings.get_mut(&mode) {
+                let macro_bindings = self.macros.entry(mode).or_default();
+
+                for (key, name) in other_macro_bindings {
+                    key_bindings.remove(&key);
+                    macro_bindings.insert(key, name);
+                }
+            }
+        }
     }
 }
 
+// Falls back to a wildcard binding when the final key is a character.
+fn find_binding<T: Clone>(bindings: &HashMap<Vec<Key>, T>, keys: &[Key]) -> Option<T> {
+    bindings
+        .get(keys)
+        .or_else(|| match keys.split_last() {
+            Some((Key::Char(_), prefix)) => {
+                let mut wildcard_keys = prefix.to_vec();
+                wildcard_keys.push(Key::AnyChar);
+
+                bindings.get(&wildcard_keys)
+            }
+            _ => None,
+        })
+        .cloned()
+}
+
 fn parse_mode_key_bindings(
     mode: &Yaml,
     commands: &HashMap<&str, Command>,
-) -> Result<HashMap<Key, SmallVec<[Command; 4]>>> {
+) -> Result<(KeyBindings, MacroBindings)> {
     let mode_key_bindings = mode
         .as_hash()
         .ok_or("Keymap mode config didn't return a hash of key bindings")?;
 
     let mut key_bindings = HashMap::new();
+    let mut macro_bindings = HashMap::new();
     for (yaml_key, yaml_command) in mode_key_bindings {
-        let key = parse_key(
+        let key = parse_key_sequence(
             yaml_key
                 .as_str()
                 .ok_or_else(|| "A keymap key couldn't be parsed as a string".to_string())?,
@@ -86,6 +152,10 @@ fn parse_mode_key_bindings(
         let mut key_commands = SmallVec::new();
 
         match *yaml_command {
+            Yaml::String(ref command) if command.starts_with(MACRO_PREFIX) => {
+                macro_bindings.insert(key, command[MACRO_PREFIX.len()..].to_string());
+                continue;
+            }
             Yaml::String(ref command) => {
                 let command_string = command.as_str();
 
@@ -116,10 +186,24 @@ fn parse_mode_key_bindings(
         key_bindings.insert(key, key_commands);
     }
 
-    Ok(key_bindings)
+    Ok((key_bindings, macro_bindings))
+}
+
+// Sequences are written as space-separated keys (e.g. "Z Z" or "space f").
+pub fn parse_key_sequence(data: &str) -> Result<Vec<Key>> {
+    if data.trim().is_empty() {
+        return Ok(vec![parse_key(data)?]);
+    }
+
+    data.split_whitespace().map(parse_key).collect()
 }
 
 fn parse_key(data: &str) -> Result<Key> {
+    // A lone hyphen is the key itself, rather than a modifier separator.
+    if data == "-" {
+        return Ok(Key::Char('-'));
+    }
+
     let mut key_components = data.split('-');
     let component = key_components
         .next()
@@ -131,10 +215,24 @@ fn parse_key(data: &str) -> Result<Key> {
             .next()
             .ok_or_else(|| format!("Keymap key \"{}\" is invalid", key))?;
 
-        match component {
-            "ctrl" => Ok(Key::Ctrl(key_char)),
+        match (component, key) {
+            ("ctrl", "left") => Ok(Key::CtrlLeft),
+            ("ctrl", "right") => Ok(Key::CtrlRight),
+            ("ctrl", "up") => Ok(Key::CtrlUp),
+            ("ctrl", "down") => Ok(Key::CtrlDown),
+            ("ctrl", _) => Ok(Key::Ctrl(key_char)),
+            ("alt", _) if key.chars().count() == 1 => Ok(Key::Alt(key_char)),
+            ("alt", _) => bail!(format!("Keymap key \"alt-{}\" is invalid", key)),
+            ("shift", "left") => Ok(Key::ShiftLeft),
+            ("shift", "right") => Ok(Key::ShiftRight),
+            ("shift", "up") => Ok(Key::ShiftUp),
+            ("shift", "down") => Ok(Key::ShiftDown),
+            ("shift", "tab") => Ok(Key::BackTab),
+            ("shift", _) => bail!(format!("Keymap key \"shift-{}\" is invalid", key)),
             _ => bail!(format!("Keymap modifier \"{}\" is invalid", component)),
         }
+    } else if let Some(number) = function_key_number(component) {
+        Ok(Key::F(number))
     } else {
         Ok(match component {
             "space" => Key::Char(' '),
@@ -163,23 +261,30 @@ fn parse_key(data: &str) -> Result<Key> {
     }
 }
 
+// Function keys are written as "f1" through "f12".
+fn function_key_number(data: &str) -> Option<u8> {
+    data.strip_prefix('f')
+        .and_then(|number| number.parse().ok())
+        .filter(|number| (1..=12).contains(number))
+}
+
 impl Deref for KeyMap {
-    type Target = HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>>;
+    type Target = HashMap<String, KeyBindings>;
 
-    fn deref(&self) -> &HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>> {
-        &self.0
+    fn deref(&self) -> &HashMap<String, KeyBindings> {
+        &self.bindings
     }
 }
 
 impl DerefMut for KeyMap {
-    fn deref_mut(&mut self) -> &mut HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>> {
-        &mut self.0
+    fn deref_mut(&mut self) -> &mut HashMap<String, KeyBindings> {
+        &mut self.bindings
     }
 }
 
-impl From<KeyMap> for HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>> {
+impl From<KeyMap> for HashMap<String, KeyBindings> {
     fn from(val: KeyMap) -> Self {
-        val.0
+        val.bindings
     }
 }
 
@@ -338,6 +443,31 @@ mod tests {
                 Key::Enter,
                 commands::cursor::move_up,
             ),
+            (
+                "normal:\n  alt-j: cursor::move_up",
+                Key::Alt('j'),
+                commands::cursor::move_up,
+            ),
+            (
+                "normal:\n  f5: cursor::move_up",
+                Key::F(5),
+                commands::cursor::move_up,
+            ),
+            (
+                "normal:\n  shift-up: cursor::move_up",
+                Key::ShiftUp,
+                commands::cursor::move_up,
+            ),
+            (
+                "normal:\n  shift-tab: cursor::move_up",
+                Key::BackTab,
+                commands::cursor::move_up,
+            ),
+            (
+                "normal:\n  ctrl-left: cursor::move_up",
+                Key::CtrlLeft,
+                commands::cursor::move_up,
+            ),
         ];
 
         for (binding, key, command) in mappings {
@@ -422,4 +552,88 @@ mod tests {
             (commands::cursor::move_down as *const usize)
         );
     }
+
+    #[test]
+    fn keymap_correctly_parses_yaml_key_sequences() {
+        let yaml_data = "normal:\n  g: cursor::move_up\n  g g: cursor::move_down\n  space f _: cursor::move_left";
+        let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+        let keymap = KeyMap::from(&yaml[0].as_hash().unwrap()).unwrap();
+
+        let command = keymap
+            .sequence_commands_for("normal", &[Key::Char('g'), Key::Char('g')])
+            .expect("Keymap doesn't contain command");
+        assert_eq!(
+            (command[0] as *const usize),
+            (commands::cursor::move_down as *const usize)
+        );
+        let wildcard_command = keymap
+            .sequence_commands_for("normal", &[Key::Char(' '), Key::Char('f'), Key::Char('x')])
+            .expect("Keymap doesn't contain command");
+        assert_eq!(
+            (wildcard_command[0] as *const usize),
+            (commands::cursor::move_left as *const usize)
+        );
+        assert!(keymap.is_prefix("normal", &[Key::Char('g')]));
+        assert!(keymap.is_prefix("normal", &[Key::Char(' '), Key::Char('f')]));
+        assert!(!keymap.is_prefix("normal", &[Key::Char('g'), Key::Char('g')]));
+    }
+
+    #[test]
+    fn keymap_parses_hyphens_in_key_sequences() {
+        let yaml_data = "normal:\n  ctrl-w -: cursor::move_up";
+        let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+        let keymap = KeyMap::from(&yaml[0].as_hash().unwrap()).unwrap();
+
+        let command = keymap
+            .sequence_commands_for("normal", &[Key::Ctrl('w'), Key::Char('-')])
+            .expect("Keymap doesn't contain command");
+        assert_eq!(
+            (command[0] as *const usize),
+            (commands::cursor::move_up as *const usize)
+        );
+    }
+
+    #[test]
+    fn keymap_binds_keys_to_macros_by_name() {
+        let yaml = YamlLoader::load_from_str("normal:\n  k: cursor::move_up").unwrap();
+        let mut keymap = KeyMap::from(yaml[0].as_hash().unwrap()).unwrap();
+        let other_yaml = YamlLoader::load_from_str("normal:\n  k: macros::quote").unwrap();
+        let other_keymap = KeyMap::from(other_yaml[0].as_hash().unwrap()).unwrap();
+
+        keymap.merge(other_keymap);
+
+        assert!(keymap.commands_for("normal", &Key::Char('k')).is_none());
+        assert_eq!(
+            keymap.sequence_macro_for("normal", &[Key::Char('k')]),
+            Some(String::from("quote"))
+        );
+    }
+
+    #[test]
+    fn keymap_rejects_unknown_shifted_keys() {
+        let yaml = YamlLoader::load_from_str("normal:\n  shift-j: cursor::move_up").unwrap();
+
+        assert!(KeyMap::from(yaml[0].as_hash().unwrap()).is_err());
+    }
+
+    #[test]
+    fn keymap_rejects_named_keys_with_alt() {
+        let yaml = YamlLoader::load_from_str("normal:\n  alt-left: cursor::move_up").unwrap();
+
+        assert!(KeyMap::from(yaml[0].as_hash().unwrap()).is_err());
+    }
+
+    #[test]
+    fn keys_are_displayed_using_keymap_names() {
+        let keys = [
+            Key::Char(' '),
+            Key::Ctrl('a'),
+            Key::Alt('j'),
+            Key::F(12),
+            Key::ShiftDown,
+        ];
+        let names: Vec<String> = keys.iter().map(Key::to_string).collect();
+
+        assert_eq!(names, vec!["space", "ctrl-a", "alt-j", "f12", "shift-down"]);
+    }
 }
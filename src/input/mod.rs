@@ -1,4 +1,6 @@
-pub use self::key_map::KeyMap;
+pub use self::key_map::{parse_key_sequence, KeyMap};
+
+use std::fmt;
 
 mod key_map;
 
@@ -17,8 +19,56 @@ pub enum Key {
     Insert,
     Esc,
     Tab,
+    BackTab,
     Enter,
     AnyChar,
     Char(char),
     Ctrl(char),
+    Alt(char),
+    F(u8),
+    ShiftLeft,
+    ShiftRight,
+    ShiftUp,
+    ShiftDown,
+    CtrlLeft,
+    CtrlRight,
+    CtrlUp,
+    CtrlDown,
+}
+
+// Keys are displayed using the same names as the keymap.
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Key::Backspace => write!(f, "backspace"),
+            Key::Left => write!(f, "left"),
+            Key::Right => write!(f, "right"),
+            Key::Up => write!(f, "up"),
+            Key::Down => write!(f, "down"),
+            Key::Home => write!(f, "home"),
+            Key::End => write!(f, "end"),
+            Key::PageUp => write!(f, "page_up"),
+            Key::PageDown => write!(f, "page_down"),
+            Key::Delete => write!(f, "delete"),
+            Key::Insert => write!(f, "insert"),
+            Key::Esc => write!(f, "escape"),
+            Key::Tab => write!(f, "tab"),
+            Key::BackTab => write!(f, "shift-tab"),
+            Key::Enter => write!(f, "enter"),
+            Key::AnyChar => write!(f, "_"),
+            Key::Char(' ') => write!(f, "space"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::Ctrl(c) => write!(f, "ctrl-{}", c),
+            Key::Alt(c) => write!(f, "alt-{}", c),
+            Key::F(n) => write!(f, "f{}", n),
+            Key::ShiftLeft => write!(f, "shift-left"),
+            Key::ShiftRight => write!(f, "shift-right"),
+            Key::ShiftUp => write!(f, "shift-up"),
+            Key::ShiftDown => write!(f, "shift-down"),
+            Key::CtrlLeft => write!(f, "ctrl-left"),
+            Key::CtrlRight => write!(f, "ctrl-right"),
+            Key::CtrlUp => write!(f, "ctrl-up"),
+            Key::CtrlDown => write!(f, "ctrl-down"),
+        }
+    }
 }
@@ -0,0 +1,95 @@
+use crate::input::Key;
+use std::mem;
+
+/// Records the keys behind the most recent change to a buffer,
+/// so that it can be replayed by `application::repeat_last_change`.
+#[derive(Default)]
+pub struct ChangeRecorder {
+    keys: Vec<Key>,
+    original_revision: Option<(usize, usize)>,
+    last_change: Vec<Key>,
+}
+
+impl ChangeRecorder {
+    pub fn new() -> ChangeRecorder {
+        ChangeRecorder::default()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.original_revision.is_some()
+    }
+
+    /// Starts recording keys typed in the buffer with the given id and revision.
+    pub fn start(&mut self, buffer_id: usize, revision: usize) {
+        self.keys.clear();
+        self.original_revision = Some((buffer_id, revision));
+    }
+
+    pub fn record(&mut self, key: Key) {
+        if self.is_recording() {
+            self.keys.push(key);
+        }
+    }
+
+    // The recorded keys only replace the last change if they modified the
+    // buffer they were typed in; switching to another buffer isn't a change.
+    pub fn finish(&mut self, buffer_id: Option<usize>, revision: usize) {
+        if let Some((original_id, original_revision)) = self.original_revision.take() {
+            if buffer_id == Some(original_id) && revision != original_revision {
+                self.last_change = mem::take(&mut self.keys);
+            }
+        }
+        self.keys.clear();
+    }
+
+    pub fn cancel(&mut self) {
+        self.original_revision = None;
+        self.keys.clear();
+    }
+
+    pub fn last_change(&self) -> &[Key] {
+        &self.last_change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChangeRecorder;
+    use crate::input::Key;
+
+    #[test]
+    fn finish_keeps_keys_that_modified_the_buffer() {
+        let mut changes = ChangeRecorder::new();
+        changes.start(0, 0);
+        changes.record(Key::Char('x'));
+        changes.finish(Some(0), 1);
+
+        changes.start(0, 1);
+        changes.record(Key::Char('j'));
+        changes.finish(Some(0), 1);
+
+        assert_eq!(changes.last_change(), &[Key::Char('x')]);
+        assert!(!changes.is_recording());
+    }
+
+    #[test]
+    fn finish_ignores_keys_that_switched_buffers() {
+        let mut changes = ChangeRecorder::new();
+        changes.start(0, 2);
+        changes.record(Key::Tab);
+        changes.finish(Some(1), 5);
+
+        assert!(changes.last_change().is_empty());
+    }
+
+    #[test]
+    fn record_ignores_keys_when_not_recording() {
+        let mut changes = ChangeRecorder::new();
+        changes.start(0, 0);
+        changes.cancel();
+        changes.record(Key::Char('x'));
+        changes.finish(Some(0), 1);
+
+        assert!(changes.last_change().is_empty());
+    }
+}
@@ -1,62 +1,129 @@
 use crate::errors::*;
+use crate::view::Terminal;
 use cli_clipboard::{ClipboardContext, ClipboardProvider};
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::sync::Arc;
 
-#[derive(Debug, PartialEq)]
+// The number of yanks and deletes kept in the clipboard history.
+const HISTORY_SIZE: usize = 20;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ClipboardContent {
     Inline(String),
     Block(String),
+    Rectangle(String),
     None,
 }
 
-pub struct Clipboard {
-    content: ClipboardContent,
-    system_clipboard: Option<ClipboardContext>,
+/// Where copied content is sent, in addition to being kept in memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClipboardBackend {
+    System,
+    Osc52,
+    Internal,
 }
 
-impl Default for Clipboard {
-    fn default() -> Self {
-        Self::new()
-    }
+enum Provider {
+    System(Box<ClipboardContext>),
+    // Written to the terminal, which sets the clipboard on the
+    // user's machine, even when running on a remote host.
+    Osc52(Arc<Box<dyn Terminal + Sync + Send + 'static>>),
+    Internal,
 }
 
-impl Clipboard {
-    pub fn new() -> Clipboard {
-        let system_clipboard = match ClipboardProvider::new() {
-            Ok(clipboard) => Some(clipboard),
-            Err(_) => None,
-        };
+pub struct Clipboard {
+    content: ClipboardContent,
+    provider: Provider,
+    registers: HashMap<char, ClipboardContent>,
+    register: Option<char>,
+    register_pending: bool,
+    history: VecDeque<ClipboardContent>,
+}
 
+impl Clipboard {
+    pub fn new(
+        backend: ClipboardBackend,
+        terminal: Arc<Box<dyn Terminal + Sync + Send + 'static>>,
+    ) -> Clipboard {
         Clipboard {
             content: ClipboardContent::None,
-            system_clipboard,
+            provider: build_provider(backend, terminal),
+            registers: HashMap::new(),
+            register: None,
+            register_pending: false,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn set_backend(
+        &mut self,
+        backend: ClipboardBackend,
+        terminal: Arc<Box<dyn Terminal + Sync + Send + 'static>>,
+    ) {
+        self.provider = build_provider(backend, terminal);
+    }
+
+    /// Directs the next read or write to a named register,
+    /// rather than the system clipboard.
+    pub fn select_register(&mut self, register: char) -> Result<()> {
+        if !register.is_ascii_lowercase() {
+            bail!(format!("\"{}\" isn't a register; use a-z", register));
+        }
+        self.register = Some(register);
+        self.register_pending = true;
+
+        Ok(())
+    }
+
+    /// Called once a command has run, so that a selected register
+    /// only applies to the command that follows its selection.
+    pub fn finish_command(&mut self) {
+        if !mem::take(&mut self.register_pending) {
+            self.register = None;
         }
     }
 
+    pub fn selected_register(&self) -> Option<char> {
+        self.register
+    }
+
+    /// Recent yanks and deletes, most recent first.
+    pub fn history(&self) -> impl Iterator<Item = &ClipboardContent> {
+        self.history.iter()
+    }
+
     pub fn get_content(&mut self) -> &ClipboardContent {
-        let new_content = match self.system_clipboard {
-            Some(ref mut clipboard) => {
-                match clipboard.get_contents() {
-                    Ok(content) => {
-                        if content.is_empty() {
-                            None
-                        } else {
-                            match self.content {
-                                ClipboardContent::Inline(ref app_content)
-                                | ClipboardContent::Block(ref app_content) => {
-                                    if content != *app_content {
-                                        Some(ClipboardContent::Inline(content))
-                                    } else {
-                                        None
-                                    }
+        if let Some(register) = self.register.take() {
+            return self
+                .registers
+                .entry(register)
+                .or_insert(ClipboardContent::None);
+        }
+
+        let new_content = match self.provider {
+            Provider::System(ref mut clipboard) => match clipboard.get_contents() {
+                Ok(content) => {
+                    if content.is_empty() {
+                        None
+                    } else {
+                        match self.content {
+                            ClipboardContent::Inline(ref app_content)
+                            | ClipboardContent::Block(ref app_content)
+                            | ClipboardContent::Rectangle(ref app_content) => {
+                                if content != *app_content {
+                                    Some(ClipboardContent::Inline(content))
+                                } else {
+                                    None
                                 }
-                                _ => Some(ClipboardContent::Inline(content)),
                             }
+                            _ => Some(ClipboardContent::Inline(content)),
                         }
                     }
-                    _ => None,
                 }
-            }
-            None => None,
+                _ => None,
+            },
+            _ => None,
         };
 
         if let Some(new_content) = new_content {
@@ -67,20 +134,137 @@ impl Clipboard {
     }
 
     pub fn set_content(&mut self, content: ClipboardContent) -> Result<()> {
+        if content != ClipboardContent::None {
+            self.history.retain(|entry| *entry != content);
+            self.history.push_front(content.clone());
+            self.history.truncate(HISTORY_SIZE);
+        }
+
+        if let Some(register) = self.register.take() {
+            self.registers.insert(register, content);
+
+            return Ok(());
+        }
         self.content = content;
 
         match self.content {
             ClipboardContent::Inline(ref app_content)
-            | ClipboardContent::Block(ref app_content) => {
-                if let Some(ref mut clipboard) = self.system_clipboard {
+            | ClipboardContent::Block(ref app_content)
+            | ClipboardContent::Rectangle(ref app_content) => match self.provider {
+                Provider::System(ref mut clipboard) => {
                     return clipboard
                         .set_contents(app_content.clone())
                         .map_err(|_| Error::from("Failed to update system clipboard"));
                 }
-            }
+                Provider::Osc52(ref terminal) => return terminal.set_clipboard(app_content),
+                Provider::Internal => (),
+            },
             _ => (),
         }
 
         Ok(())
     }
 }
+
+// The system clipboard isn't available without a display server,
+// in which case content is only kept in memory.
+fn build_provider(
+    backend: ClipboardBackend,
+    terminal: Arc<Box<dyn Terminal + Sync + Send + 'static>>,
+) -> Provider {
+    match backend {
+        ClipboardBackend::System => match ClipboardProvider::new() {
+            Ok(clipboard) => Provider::System(Box::new(clipboard)),
+            Err(_) => Provider::Internal,
+        },
+        ClipboardBackend::Osc52 => Provider::Osc52(terminal),
+        ClipboardBackend::Internal => Provider::Internal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clipboard, ClipboardBackend, ClipboardContent, HISTORY_SIZE};
+    use crate::view::{Terminal, TestTerminal};
+    use std::sync::Arc;
+
+    #[test]
+    fn named_registers_are_kept_separate_from_the_clipboard() {
+        let mut clipboard = test_clipboard();
+        clipboard
+            .set_content(ClipboardContent::Inline("nexedit".to_string()))
+            .unwrap();
+        clipboard.select_register('a').unwrap();
+        clipboard
+            .set_content(ClipboardContent::Block("editor\n".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            *clipboard.get_content(),
+            ClipboardContent::Inline("nexedit".to_string())
+        );
+        clipboard.select_register('a').unwrap();
+        assert_eq!(
+            *clipboard.get_content(),
+            ClipboardContent::Block("editor\n".to_string())
+        );
+        assert!(clipboard.select_register('A').is_err());
+    }
+
+    #[test]
+    fn selected_registers_are_cleared_after_the_next_command() {
+        let mut clipboard = test_clipboard();
+        clipboard.select_register('a').unwrap();
+        clipboard.finish_command();
+        assert_eq!(clipboard.selected_register(), Some('a'));
+
+        clipboard.finish_command();
+        assert_eq!(clipboard.selected_register(), None);
+    }
+
+    #[test]
+    fn history_keeps_the_most_recent_unique_entries_first() {
+        let mut clipboard = test_clipboard();
+        for i in 0..HISTORY_SIZE + 5 {
+            clipboard
+                .set_content(ClipboardContent::Inline(i.to_string()))
+                .unwrap();
+        }
+        clipboard
+            .set_content(ClipboardContent::Inline("10".to_string()))
+            .unwrap();
+
+        let history: Vec<&ClipboardContent> = clipboard.history().collect();
+        assert_eq!(history.len(), HISTORY_SIZE);
+        assert_eq!(*history[0], ClipboardContent::Inline("10".to_string()));
+        assert_eq!(*history[1], ClipboardContent::Inline("24".to_string()));
+        assert_eq!(
+            clipboard
+                .history()
+                .filter(|entry| **entry == ClipboardContent::Inline("10".to_string()))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn osc52_backend_keeps_content_for_pasting() {
+        let terminal: Box<dyn Terminal + Sync + Send> = Box::new(TestTerminal::new());
+        let mut clipboard = Clipboard::new(ClipboardBackend::Osc52, Arc::new(terminal));
+        clipboard
+            .set_content(ClipboardContent::Block("nexedit\n".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            *clipboard.get_content(),
+            ClipboardContent::Block("nexedit\n".to_string())
+        );
+    }
+
+    // Avoids depending on a system clipboard being available.
+    fn test_clipboard() -> Clipboard {
+        let terminal: Box<dyn Terminal + Sync + Send> = Box::new(TestTerminal::new());
+
+        Clipboard::new(ClipboardBackend::Internal, Arc::new(terminal))
+    }
+}
@@ -0,0 +1,157 @@
+use scribe::buffer::Position;
+use scribe::Buffer;
+use std::cmp::Ordering;
+
+/// Secondary cursors for the current buffer, each with
+/// the anchor of its selection when in select mode.
+#[derive(Default)]
+pub struct Cursors {
+    buffer_id: Option<usize>,
+    positions: Vec<Position>,
+    anchors: Vec<Option<Position>>,
+}
+
+impl Cursors {
+    pub fn new() -> Cursors {
+        Cursors::default()
+    }
+
+    pub fn positions(&self, buffer: &Buffer) -> &[Position] {
+        if buffer.id == self.buffer_id {
+            &self.positions
+        } else {
+            &[]
+        }
+    }
+
+    /// The selection anchors for the secondary cursors, in the same order.
+    pub fn anchors(&self, buffer: &Buffer) -> &[Option<Position>] {
+        if buffer.id == self.buffer_id {
+            &self.anchors
+        } else {
+            &[]
+        }
+    }
+
+    pub fn add(&mut self, buffer: &Buffer, position: Position) {
+        self.add_selection(buffer, position, None);
+    }
+
+    pub fn add_selection(&mut self, buffer: &Buffer, position: Position, anchor: Option<Position>) {
+        let mut selections = self.selections(buffer);
+        selections.push((position, anchor));
+
+        self.set_selections(buffer, selections);
+    }
+
+    pub fn set(&mut self, buffer: &Buffer, positions: Vec<Position>) {
+        self.set_selections(
+            buffer,
+            positions
+                .into_iter()
+                .map(|position| (position, None))
+                .collect(),
+        );
+    }
+
+    pub fn set_selections(
+        &mut self,
+        buffer: &Buffer,
+        mut selections: Vec<(Position, Option<Position>)>,
+    ) {
+        selections.retain(|(position, _)| *position != *buffer.cursor);
+        selections.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        selections.dedup_by(|(a, _), (b, _)| a == b);
+
+        self.buffer_id = buffer.id;
+        (self.positions, self.anchors) = selections.into_iter().unzip();
+    }
+
+    /// Starts an empty selection at each cursor.
+    pub fn anchor_selections(&mut self) {
+        self.anchors = self.positions.iter().copied().map(Some).collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.anchors.clear();
+    }
+
+    fn selections(&self, buffer: &Buffer) -> Vec<(Position, Option<Position>)> {
+        self.positions(buffer)
+            .iter()
+            .copied()
+            .zip(self.anchors(buffer).iter().copied())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursors;
+    use scribe::buffer::Position;
+    use scribe::Buffer;
+
+    #[test]
+    fn set_sorts_and_removes_duplicate_and_primary_positions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\nnexedit");
+        let mut cursors = Cursors::new();
+
+        cursors.set(
+            &buffer,
+            vec![
+                Position { line: 1, offset: 2 },
+                Position { line: 0, offset: 0 },
+                Position { line: 0, offset: 4 },
+                Position { line: 1, offset: 2 },
+            ],
+        );
+
+        assert_eq!(
+            cursors.positions(&buffer),
+            &[
+                Position { line: 0, offset: 4 },
+                Position { line: 1, offset: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_are_scoped_to_a_buffer() {
+        let buffer = Buffer::new();
+        let mut other_buffer = Buffer::new();
+        other_buffer.id = Some(1);
+        let mut cursors = Cursors::new();
+
+        cursors.add(&buffer, Position { line: 0, offset: 1 });
+
+        assert!(cursors.positions(&other_buffer).is_empty());
+    }
+
+    #[test]
+    fn set_selections_keeps_anchors_with_their_positions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\nnexedit");
+        let mut cursors = Cursors::new();
+
+        cursors.add_selection(
+            &buffer,
+            Position { line: 1, offset: 3 },
+            Some(Position { line: 1, offset: 0 }),
+        );
+        cursors.add(&buffer, Position { line: 0, offset: 4 });
+
+        assert_eq!(
+            cursors.positions(&buffer),
+            &[
+                Position { line: 0, offset: 4 },
+                Position { line: 1, offset: 3 },
+            ]
+        );
+        assert_eq!(
+            cursors.anchors(&buffer),
+            &[None, Some(Position { line: 1, offset: 0 })]
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use crate::input::Key;
 use crate::models::application::modes::open::Index;
+use crate::models::application::modes::GrepResult;
+use std::path::PathBuf;
 
 #[derive(Debug, PartialEq)]
 pub enum Event {
     Key(Key),
     Resize,
     OpenModeIndexComplete(Index),
+    GrepModeIndexComplete(Vec<PathBuf>),
+    GrepModeResults {
+        search_id: usize,
+        results: Vec<GrepResult>,
+        complete: bool,
+    },
 }
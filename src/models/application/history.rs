@@ -0,0 +1,160 @@
+use crate::errors::*;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+const HISTORY_SIZE: usize = 100;
+
+pub struct History {
+    entries: VecDeque<String>,
+    path: Option<PathBuf>,
+    position: Option<usize>,
+    draft: String,
+}
+
+impl History {
+    pub fn new(path: Option<PathBuf>) -> History {
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|data| {
+                data.lines()
+                    .filter(|line| !line.is_empty())
+                    .take(HISTORY_SIZE)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        History {
+            entries,
+            path,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    pub fn push<T: Into<String>>(&mut self, entry: T) -> Result<()> {
+        let entry = entry.into();
+        self.reset();
+
+        if entry.is_empty() || entry.contains('\n') || self.entries.front() == Some(&entry) {
+            return Ok(());
+        }
+
+        self.entries.retain(|existing| *existing != entry);
+        self.entries.push_front(entry);
+        self.entries.truncate(HISTORY_SIZE);
+
+        self.save()
+    }
+
+    pub fn previous(&mut self, input: &str) -> Option<&str> {
+        let position = self.position.map(|position| position + 1).unwrap_or(0);
+        if position >= self.entries.len() {
+            return None;
+        }
+
+        if self.position.is_none() {
+            self.draft = input.to_string();
+        }
+        self.position = Some(position);
+
+        self.entries.get(position).map(|entry| entry.as_str())
+    }
+
+    pub fn next(&mut self) -> Option<&str> {
+        match self.position? {
+            0 => {
+                self.position = None;
+
+                Some(&self.draft)
+            }
+            position => {
+                self.position = Some(position - 1);
+
+                self.entries.get(position - 1).map(|entry| entry.as_str())
+            }
+        }
+    }
+
+    /// Entries from most to least recent.
+    pub fn entries(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.as_str())
+    }
+
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            let data: Vec<&str> = self.entries.iter().map(|entry| entry.as_str()).collect();
+            fs::write(path, data.join("\n")).chain_err(|| "Couldn't save history")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, HISTORY_SIZE};
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn previous_and_next_cycle_through_entries_and_restore_the_draft() {
+        let mut history = History::new(None);
+        history.push("first").unwrap();
+        history.push("second").unwrap();
+
+        assert_eq!(history.previous("draft"), Some("second"));
+        assert_eq!(history.previous("second"), Some("first"));
+        assert_eq!(history.previous("first"), None);
+        assert_eq!(history.next(), Some("second"));
+        assert_eq!(history.next(), Some("draft"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn push_moves_duplicate_entries_to_the_front() {
+        let mut history = History::new(None);
+        history.push("first").unwrap();
+        history.push("second").unwrap();
+        history.push("first").unwrap();
+
+        assert_eq!(history.previous(""), Some("first"));
+        assert_eq!(history.previous(""), Some("second"));
+        assert_eq!(history.previous(""), None);
+    }
+
+    #[test]
+    fn push_discards_the_oldest_entries_when_full() {
+        let mut history = History::new(None);
+        for entry in 0..HISTORY_SIZE + 1 {
+            history.push(entry.to_string()).unwrap();
+        }
+
+        let mut oldest = None;
+        while let Some(entry) = history.previous("") {
+            oldest = Some(entry.to_string());
+        }
+
+        assert_eq!(oldest, Some(String::from("1")));
+    }
+
+    #[test]
+    fn push_persists_entries_to_the_history_path() {
+        let path = env::temp_dir().join(format!("nexedit_history_{}", process::id()));
+        let _ = fs::remove_file(&path);
+
+        History::new(Some(path.clone())).push("query").unwrap();
+        let mut history = History::new(Some(path.clone()));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(history.previous(""), Some("query"));
+    }
+}
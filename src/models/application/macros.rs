@@ -0,0 +1,108 @@
+use crate::input::Key;
+use std::collections::HashMap;
+use std::mem;
+
+/// Macro registers, filled by recording the keys typed by the user.
+#[derive(Default)]
+pub struct MacroRecorder {
+    registers: HashMap<char, Vec<Key>>,
+    recording: Option<(char, Vec<Key>)>,
+    sequence: Vec<Key>,
+    sequence_recorded: bool,
+    playing: Vec<String>,
+}
+
+impl MacroRecorder {
+    pub fn new() -> MacroRecorder {
+        MacroRecorder::default()
+    }
+
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    pub fn start(&mut self, register: char) {
+        self.recording = Some((register, Vec::new()));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((register, keys)) = self.recording.take() {
+            self.registers.insert(register, keys);
+        }
+    }
+
+    pub fn register(&self, register: char) -> Option<&[Key]> {
+        self.registers.get(&register).map(Vec::as_slice)
+    }
+
+    // Keys are recorded a whole key sequence at a time, so that the
+    // sequences starting and stopping a recording are left out of it.
+    pub fn push_key(&mut self, key: Key) {
+        if self.sequence.is_empty() {
+            self.sequence_recorded = self.recording.is_some();
+        }
+        self.sequence.push(key);
+    }
+
+    pub fn end_sequence(&mut self) {
+        let keys = mem::take(&mut self.sequence);
+
+        if self.sequence_recorded {
+            if let Some((_, ref mut recorded_keys)) = self.recording {
+                recorded_keys.extend(keys);
+            }
+        }
+    }
+
+    /// Marks a macro as playing, returning false if it already is
+    /// (i.e. the macro is trying to replay itself).
+    pub fn start_playing(&mut self, name: &str) -> bool {
+        if self.playing.iter().any(|playing| playing == name) {
+            return false;
+        }
+        self.playing.push(name.to_string());
+
+        true
+    }
+
+    pub fn stop_playing(&mut self, name: &str) {
+        self.playing.retain(|playing| playing != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MacroRecorder;
+    use crate::input::Key;
+
+    #[test]
+    fn sequences_that_start_and_stop_recording_are_left_out() {
+        let mut macros = MacroRecorder::new();
+
+        macros.push_key(Key::Char('W'));
+        macros.push_key(Key::Char('a'));
+        macros.start('a');
+        macros.end_sequence();
+
+        macros.push_key(Key::Char('x'));
+        macros.end_sequence();
+
+        macros.push_key(Key::Char('W'));
+        macros.push_key(Key::Char('W'));
+        macros.stop();
+        macros.end_sequence();
+
+        assert_eq!(macros.register('a'), Some(&[Key::Char('x')][..]));
+        assert_eq!(macros.recording_register(), None);
+    }
+
+    #[test]
+    fn start_playing_rejects_macros_that_are_already_playing() {
+        let mut macros = MacroRecorder::new();
+
+        assert!(macros.start_playing("a"));
+        assert!(!macros.start_playing("a"));
+        macros.stop_playing("a");
+        assert!(macros.start_playing("a"));
+    }
+}
@@ -1,39 +1,58 @@
+mod changes;
 mod clipboard;
+mod cursors;
 mod event;
+mod history;
+mod macros;
 pub mod modes;
 mod preferences;
+mod session;
 
-pub use self::clipboard::ClipboardContent;
+pub use self::changes::ChangeRecorder;
+pub use self::clipboard::{ClipboardBackend, ClipboardContent};
+pub use self::cursors::Cursors;
 pub use self::event::Event;
+pub use self::history::History;
+pub use self::macros::MacroRecorder;
 pub use self::preferences::Preferences;
+pub use self::session::{Session, SessionBuffer};
 
 use self::clipboard::Clipboard;
 use self::modes::*;
 use crate::commands;
 use crate::errors::*;
+use crate::input::Key;
 use crate::presenters;
 use crate::view::View;
 use git2::Repository;
+use scribe::buffer::{Position, Range};
 use scribe::{Buffer, Workspace};
 use std::cell::RefCell;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 
 pub enum Mode {
+    BufferList(BufferListMode),
+    ClipboardHistory(ClipboardHistoryMode),
     Confirm(ConfirmMode),
     Command(CommandMode),
     Exit,
+    Grep(GrepMode),
     Insert,
     Jump(JumpMode),
     LineJump(LineJumpMode),
     Path(PathMode),
+    RecentFiles(RecentFilesMode),
     Normal,
     Open(OpenMode),
+    Replace(ReplaceMode),
     Select(SelectMode),
     SelectLine(SelectLineMode),
+    SelectBlock(SelectBlockMode),
     Search(SearchMode),
+    Surround(SurroundMode),
     SymbolJump(SymbolJumpMode),
     Syntax(SyntaxMode),
     Theme(ThemeMode),
@@ -42,7 +61,22 @@ pub enum Mode {
 pub struct Application {
     pub mode: Mode,
     pub workspace: Workspace,
+    pub recent_buffers: Vec<usize>,
+    pub cursors: Cursors,
+    pub count: Option<usize>,
+    pub pending_keys: Vec<Key>,
+    pub changes: ChangeRecorder,
+    held_operation_groups: usize,
+    pub macros: MacroRecorder,
     pub search_query: Option<String>,
+    pub search_regex: bool,
+    pub search_highlight: bool,
+    search_matches: Option<(SearchKey, Option<Vec<Range>>)>,
+    pub search_history: History,
+    pub path_history: History,
+    pub line_jump_history: History,
+    pub recent_files: History,
+    pub session_path: Option<PathBuf>,
     pub view: View,
     pub clipboard: Clipboard,
     pub repository: Option<Repository>,
@@ -58,14 +92,32 @@ impl Application {
 
         let (event_channel, events) = mpsc::channel();
         let mut view = View::new(preferences.clone(), event_channel.clone())?;
-        let clipboard = Clipboard::new();
+        let clipboard = Clipboard::new(preferences.borrow().clipboard(), view.terminal());
 
-        let workspace = create_workspace(&mut view, &preferences.borrow(), args)?;
+        let mut recent_files = History::new(history_path("files"));
+        let workspace =
+            create_workspace(&mut view, &preferences.borrow(), &mut recent_files, args)?;
 
-        Ok(Application {
+        let session_path = session_path(&workspace.path);
+        let mut app = Application {
             mode: Mode::Normal,
             workspace,
+            recent_buffers: Vec::new(),
+            cursors: Cursors::new(),
+            count: None,
+            pending_keys: Vec::new(),
+            changes: ChangeRecorder::new(),
+            held_operation_groups: 0,
+            macros: MacroRecorder::new(),
             search_query: None,
+            search_regex: false,
+            search_highlight: false,
+            search_matches: None,
+            search_history: History::new(history_path("search")),
+            path_history: History::new(history_path("path")),
+            line_jump_history: History::new(history_path("line_jump")),
+            recent_files,
+            session_path,
             view,
             clipboard,
             repository: Repository::discover(env::current_dir()?).ok(),
@@ -73,13 +125,25 @@ impl Application {
             preferences,
             event_channel,
             events,
-        })
+        };
+
+        // Sessions are only restored when no files were specified. A
+        // broken session is reported, rather than preventing startup.
+        let restore_session = app.workspace.current_buffer.is_none()
+            && app.preferences.borrow().session()
+            && app.session_path.as_ref().is_some_and(|path| path.exists());
+        if restore_session {
+            app.error = commands::session::restore(&mut app).err();
+        }
+
+        Ok(app)
     }
 
     pub fn run(&mut self) -> Result<()> {
         loop {
             self.render()?;
             self.wait_for_event()?;
+            self.track_buffer_use();
 
             if let Mode::Exit = self.mode {
                 break;
@@ -100,6 +164,21 @@ impl Application {
     }
 
     fn present(&mut self) -> Result<()> {
+        let cursors = self.secondary_cursors();
+        self.view.key_hint = if !self.pending_keys.is_empty() {
+            let keys: Vec<String> = self.pending_keys.iter().map(Key::to_string).collect();
+
+            Some(format!(" {} ", keys.join(" ")))
+        } else if let Some(register) = self.clipboard.selected_register() {
+            Some(format!(" \"{} ", register))
+        } else {
+            self.macros
+                .recording_register()
+                .map(|register| format!(" recording {} ", register))
+        };
+
+        presenters::panes::display(&mut self.workspace, &mut self.view, &self.repository)?;
+
         match self.mode {
             Mode::Confirm(_) => {
                 presenters::modes::confirm::display(&mut self.workspace, &mut self.view)
@@ -107,13 +186,27 @@ impl Application {
             Mode::Command(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
-            Mode::Insert => presenters::modes::insert::display(&mut self.workspace, &mut self.view),
+            Mode::Grep(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Insert => {
+                let highlights = self.search_highlights();
+                presenters::modes::insert::display(
+                    &mut self.workspace,
+                    &mut self.view,
+                    highlights.as_deref(),
+                    &cursors,
+                )
+            }
             Mode::Open(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Search(ref mode) => {
                 presenters::modes::search::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Replace(ref mode) => {
+                presenters::modes::replace::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::Jump(ref mut mode) => {
                 presenters::modes::jump::display(&mut self.workspace, mode, &mut self.view)
             }
@@ -123,23 +216,50 @@ impl Application {
             Mode::Path(ref mode) => {
                 presenters::modes::path::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Surround(ref mode) => {
+                presenters::modes::surround::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::SymbolJump(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Syntax(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::BufferList(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::ClipboardHistory(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::RecentFiles(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::Select(ref mode) => {
-                presenters::modes::select::display(&mut self.workspace, mode, &mut self.view)
+                presenters::modes::select::display(&mut self.workspace, mode, &mut self.view, &cursors)
             }
             Mode::SelectLine(ref mode) => {
                 presenters::modes::select_line::display(&mut self.workspace, mode, &mut self.view)
             }
-            Mode::Normal => presenters::modes::normal::display(
-                &mut self.workspace,
-                &mut self.view,
-                &self.repository,
-            ),
+            Mode::SelectBlock(ref mode) => {
+                let tab_width = self.tab_width();
+                presenters::modes::select_block::display(
+                    &mut self.workspace,
+                    mode,
+                    &mut self.view,
+                    tab_width,
+                )
+            }
+            Mode::Normal => {
+                let highlights = self.search_highlights();
+                presenters::modes::normal::display(
+                    &mut self.workspace,
+                    &mut self.view,
+                    &self.repository,
+                    highlights.as_deref(),
+                    &cursors,
+                    self.count,
+                )
+            }
             Mode::Theme(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
@@ -147,15 +267,112 @@ impl Application {
         }
     }
 
+    /// Moves the current buffer to the front of the recently used list.
+    pub fn track_buffer_use(&mut self) {
+        if let Some(id) = self.workspace.current_buffer.as_ref().and_then(|b| b.id) {
+            self.recent_buffers.retain(|recent| *recent != id);
+            self.recent_buffers.insert(0, id);
+        }
+    }
+
+    /// Starts an operation group in the current buffer that stays open, even
+    /// through leaving insert mode, until it's released as often as it was held.
+    /// Commands that run other commands use this to undo them as a single step.
+    pub fn hold_operation_group(&mut self) {
+        if let Some(buffer) = self.workspace.current_buffer.as_mut() {
+            buffer.start_operation_group();
+        }
+        self.held_operation_groups += 1;
+    }
+
+    pub fn release_operation_group(&mut self) {
+        self.held_operation_groups = self.held_operation_groups.saturating_sub(1);
+
+        // Insert mode ends its own group when it's finished.
+        if self.held_operation_groups == 0 && !matches!(self.mode, Mode::Insert) {
+            if let Some(buffer) = self.workspace.current_buffer.as_mut() {
+                buffer.end_operation_group();
+            }
+        }
+    }
+
+    pub fn operation_group_held(&self) -> bool {
+        self.held_operation_groups > 0
+    }
+
+    pub fn tab_width(&self) -> usize {
+        let path = self
+            .workspace
+            .current_buffer
+            .as_ref()
+            .and_then(|buffer| buffer.path.as_ref());
+
+        self.preferences.borrow().tab_width(path)
+    }
+
+    fn secondary_cursors(&self) -> Vec<Position> {
+        self.workspace
+            .current_buffer
+            .as_ref()
+            .map(|buffer| self.cursors.positions(buffer).to_vec())
+            .unwrap_or_default()
+    }
+
+    // Matches are kept between renders, until the query, its options,
+    // or the buffer they were found in changes.
+    fn search_highlights(&mut self) -> Option<Vec<Range>> {
+        if !self.search_highlight {
+            return None;
+        }
+
+        let buffer = self.workspace.current_buffer.as_ref()?;
+        let key = SearchKey {
+            buffer_id: buffer.id,
+            revision: self.view.revision(buffer),
+            query: self.search_query.clone()?,
+            regex: self.search_regex,
+            case: self.preferences.borrow().search_case(),
+        };
+        let cached = self
+            .search_matches
+            .as_ref()
+            .is_some_and(|(cached_key, _)| *cached_key == key);
+
+        if !cached {
+            let mut mode = SearchMode::new(Some(key.query.clone()), key.regex, key.case);
+            let ranges = mode
+                .search(buffer)
+                .ok()
+                .and(mode.results)
+                .map(|results| results.to_vec());
+            self.search_matches = Some((key, ranges));
+        }
+
+        self.search_matches
+            .as_ref()
+            .and_then(|(_, ranges)| ranges.clone())
+    }
+
     fn wait_for_event(&mut self) -> Result<()> {
-        let event = self
-            .events
-            .recv()
-            .chain_err(|| "Error receiving application event")?;
+        let event = if self.pending_keys.is_empty() {
+            self.events
+                .recv()
+                .chain_err(|| "Error receiving application event")?
+        } else {
+            // Give up on a partially entered key sequence if it isn't finished in time.
+            let timeout = self.preferences.borrow().key_sequence_timeout();
+            match self.events.recv_timeout(timeout) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.error = commands::application::handle_key_timeout(self).err();
+                    return Ok(());
+                }
+                Err(error) => return Err(error).chain_err(|| "Error receiving application event"),
+            }
+        };
         match event {
             Event::Key(key) => {
-                self.view.last_key = Some(key);
-                self.error = commands::application::handle_input(self).err();
+                self.error = commands::application::handle_key(self, key).err();
             }
             Event::Resize => {}
             Event::OpenModeIndexComplete(index) => {
@@ -165,6 +382,22 @@ impl Application {
                     open_mode.search();
                 }
             }
+            Event::GrepModeIndexComplete(index) => {
+                if let Mode::Grep(ref mut grep_mode) = self.mode {
+                    grep_mode.set_index(index);
+
+                    grep_mode.search();
+                }
+            }
+            Event::GrepModeResults {
+                search_id,
+                results,
+                complete,
+            } => {
+                if let Mode::Grep(ref mut grep_mode) = self.mode {
+                    grep_mode.add_results(search_id, results, complete);
+                }
+            }
         }
 
         Ok(())
@@ -193,6 +426,13 @@ impl Application {
                     Some("search_select")
                 }
             }
+            Mode::Grep(ref mode) => {
+                if mode.insert_mode() {
+                    Some("search_select_insert")
+                } else {
+                    Some("search_select")
+                }
+            }
             Mode::Theme(ref mode) => {
                 if mode.insert_mode() {
                     Some("search_select_insert")
@@ -207,14 +447,37 @@ impl Application {
                     Some("search_select")
                 }
             }
+            Mode::BufferList(ref mode) => {
+                if mode.insert_mode() {
+                    Some("search_select_insert")
+                } else {
+                    Some("search_select")
+                }
+            }
+            Mode::ClipboardHistory(ref mode) => {
+                if mode.insert_mode() {
+                    Some("search_select_insert")
+                } else {
+                    Some("search_select")
+                }
+            }
+            Mode::RecentFiles(ref mode) => {
+                if mode.insert_mode() {
+                    Some("search_select_insert")
+                } else {
+                    Some("search_select")
+                }
+            }
             Mode::Normal => Some("normal"),
             Mode::Path(_) => Some("path"),
+            Mode::Surround(_) => Some("surround"),
             Mode::Confirm(_) => Some("confirm"),
             Mode::Insert => Some("insert"),
             Mode::Jump(_) => Some("jump"),
             Mode::LineJump(_) => Some("line_jump"),
             Mode::Select(_) => Some("select"),
             Mode::SelectLine(_) => Some("select_line"),
+            Mode::SelectBlock(_) => Some("select_block"),
             Mode::Search(ref mode) => {
                 if mode.insert_mode() {
                     Some("search_insert")
@@ -222,11 +485,28 @@ impl Application {
                     Some("search")
                 }
             }
+            Mode::Replace(ref mode) => {
+                if mode.insert_mode() {
+                    Some("replace_insert")
+                } else {
+                    Some("replace")
+                }
+            }
             Mode::Exit => None,
         }
     }
 }
 
+// Identifies the search that produced a set of cached matches.
+#[derive(PartialEq)]
+struct SearchKey {
+    buffer_id: Option<usize>,
+    revision: usize,
+    query: String,
+    regex: bool,
+    case: SearchCase,
+}
+
 fn initialize_preferences() -> Rc<RefCell<Preferences>> {
     Rc::new(RefCell::new(
         Preferences::load().unwrap_or_else(|_| Preferences::new(None)),
@@ -236,6 +516,7 @@ fn initialize_preferences() -> Rc<RefCell<Preferences>> {
 fn create_workspace(
     view: &mut View,
     preferences: &Preferences,
+    recent_files: &mut History,
     args: &[String],
 ) -> Result<Workspace> {
     let mut path_args = args.iter().skip(1).peekable();
@@ -288,7 +569,14 @@ fn create_workspace(
         };
 
         workspace.add_buffer(argument_buffer);
-        view.initialize_buffer(workspace.current_buffer.as_mut().unwrap())?;
+        let buffer = workspace.current_buffer.as_mut().unwrap();
+        view.initialize_buffer(buffer)?;
+
+        // Recent files are a convenience; failing to record
+        // one shouldn't stop the editor from starting.
+        if let Some(ref path) = buffer.path {
+            let _ = recent_files.push(path.to_string_lossy());
+        }
     }
 
     Ok(workspace)
@@ -304,10 +592,31 @@ fn user_syntax_path() -> Result<Option<PathBuf>> {
     Ok(None)
 }
 
+#[cfg(not(any(test, feature = "bench")))]
+fn history_path(name: &str) -> Option<PathBuf> {
+    Preferences::history_path().ok().map(|path| path.join(name))
+}
+
+#[cfg(any(test, feature = "bench"))]
+fn history_path(_: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(any(test, feature = "bench")))]
+fn session_path(workspace: &Path) -> Option<PathBuf> {
+    Preferences::session_path(workspace).ok()
+}
+
+#[cfg(any(test, feature = "bench"))]
+fn session_path(_: &Path) -> Option<PathBuf> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::preferences::Preferences;
-    use super::Application;
+    use super::{Application, History};
+    use crate::util;
     use crate::view::View;
 
     use scribe::Buffer;
@@ -339,6 +648,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_highlights_are_refreshed_when_the_buffer_changes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit");
+        util::add_buffer(buffer, &mut app).unwrap();
+        app.search_query = Some(String::from("edit"));
+        app.search_highlight = true;
+        assert_eq!(app.search_highlights().map(|ranges| ranges.len()), Some(1));
+
+        app.workspace
+            .current_buffer
+            .as_mut()
+            .unwrap()
+            .insert("edit ");
+
+        assert_eq!(app.search_highlights().map(|ranges| ranges.len()), Some(2));
+    }
+
+    #[test]
+    fn application_records_file_arguments_as_recent_files() {
+        let application =
+            Application::new(&vec![String::new(), String::from("Cargo.lock")]).unwrap();
+        let path = application.workspace.current_buffer.as_ref().unwrap().path.clone();
+
+        assert_eq!(
+            application.recent_files.entries().next().map(Path::new),
+            path.as_deref()
+        );
+    }
+
     #[test]
     fn application_uses_file_arguments_to_create_new_buffers_when_files_do_not_exist() {
         let application =
@@ -368,7 +708,13 @@ mod tests {
         let mut view = View::new(preferences.clone(), event_channel.clone()).unwrap();
 
         let args = vec![String::new(), String::from("src/test.xyz")];
-        let workspace = super::create_workspace(&mut view, &preferences.borrow(), &args).unwrap();
+        let workspace = super::create_workspace(
+            &mut view,
+            &preferences.borrow(),
+            &mut History::new(None),
+            &args,
+        )
+        .unwrap();
 
         assert_eq!(
             workspace
@@ -0,0 +1,179 @@
+use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+use crate::util::SelectableVec;
+use fragment;
+use fragment::matching::AsStr;
+use std::fmt;
+use std::slice::Iter;
+
+pub struct BufferListMode {
+    insert: bool,
+    input: String,
+    entries: Vec<BufferEntry>,
+    results: SelectableVec<BufferEntry>,
+    config: SearchSelectConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferEntry {
+    pub id: usize,
+    pub path: String,
+    pub modified: bool,
+    pub status: String,
+}
+
+// Modified buffers are marked with an asterisk, as they are in the status line.
+impl fmt::Display for BufferEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if self.modified {
+            write!(f, "*")?;
+        }
+        if !self.status.is_empty() {
+            write!(f, " {}", self.status)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AsStr for BufferEntry {
+    fn as_str(&self) -> &str {
+        &self.path
+    }
+}
+
+impl BufferListMode {
+    /// Builds the mode using entries ordered from most to least recently used.
+    pub fn new(entries: Vec<BufferEntry>, config: SearchSelectConfig) -> BufferListMode {
+        BufferListMode {
+            insert: true,
+            input: String::new(),
+            entries,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+
+    /// Drops a closed buffer's entry, refreshing the results.
+    pub fn remove(&mut self, id: usize) {
+        self.entries.retain(|entry| entry.id != id);
+        self.search();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for BufferListMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BUFFERS")
+    }
+}
+
+impl SearchSelectMode<BufferEntry> for BufferListMode {
+    fn search(&mut self) {
+        // Without a query, buffers are listed from most to least recently used.
+        let results = if self.input.is_empty() {
+            self.entries
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fragment::matching::find(&self.input, &self.entries, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<BufferEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&BufferEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results.is_empty() {
+            Some(String::from("No matching buffers found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferEntry, BufferListMode};
+    use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+
+    fn entry(id: usize, path: &str, modified: bool) -> BufferEntry {
+        BufferEntry {
+            id,
+            path: path.to_string(),
+            modified,
+            status: String::from("[ok]"),
+        }
+    }
+
+    #[test]
+    fn search_lists_every_buffer_in_order_without_a_query() {
+        let entries = vec![entry(2, "src/main.rs", true), entry(0, "Cargo.toml", false)];
+        let mut mode = BufferListMode::new(entries, SearchSelectConfig::default());
+        mode.search();
+
+        let results: Vec<String> = mode.results().map(|entry| entry.to_string()).collect();
+        assert_eq!(results, vec!["src/main.rs* [ok]", "Cargo.toml [ok]"]);
+
+        mode.query().push_str("toml");
+        mode.search();
+        assert_eq!(mode.selection().map(|entry| entry.id), Some(0));
+    }
+
+    #[test]
+    fn remove_drops_the_buffer_from_the_results() {
+        let entries = vec![entry(2, "src/main.rs", false), entry(0, "Cargo.toml", false)];
+        let mut mode = BufferListMode::new(entries, SearchSelectConfig::default());
+        mode.search();
+
+        mode.remove(2);
+        assert_eq!(mode.selection().map(|entry| entry.id), Some(0));
+
+        mode.remove(0);
+        assert!(mode.is_empty());
+        assert_eq!(mode.message(), Some(String::from("No matching buffers found.")));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+use crate::models::application::ClipboardContent;
+use crate::util::SelectableVec;
+use fragment;
+use fragment::matching::AsStr;
+use std::fmt;
+use std::slice::Iter;
+
+pub struct ClipboardHistoryMode {
+    insert: bool,
+    input: String,
+    entries: Vec<ClipboardEntry>,
+    results: SelectableVec<ClipboardEntry>,
+    config: SearchSelectConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipboardEntry {
+    pub content: ClipboardContent,
+    text: String,
+}
+
+impl ClipboardEntry {
+    pub fn new(content: ClipboardContent) -> Option<ClipboardEntry> {
+        let text = match content {
+            ClipboardContent::Inline(ref text)
+            | ClipboardContent::Block(ref text)
+            | ClipboardContent::Rectangle(ref text) => text.clone(),
+            ClipboardContent::None => return None,
+        };
+
+        Some(ClipboardEntry { content, text })
+    }
+}
+
+// Entries are listed using their first line, noting how many more there are.
+impl fmt::Display for ClipboardEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = self.text.lines();
+        write!(f, "{}", lines.next().unwrap_or_default())?;
+
+        let remaining_lines = lines.count();
+        if remaining_lines > 0 {
+            write!(f, " (+{} lines)", remaining_lines)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl AsStr for ClipboardEntry {
+    fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl ClipboardHistoryMode {
+    pub fn new(entries: Vec<ClipboardEntry>, config: SearchSelectConfig) -> ClipboardHistoryMode {
+        ClipboardHistoryMode {
+            insert: false,
+            input: String::new(),
+            entries,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for ClipboardHistoryMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CLIPBOARD HISTORY")
+    }
+}
+
+impl SearchSelectMode<ClipboardEntry> for ClipboardHistoryMode {
+    fn search(&mut self) {
+        // Without a query, the entries are listed from most to least recent.
+        let results = if self.input.is_empty() {
+            self.entries
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fragment::matching::find(&self.input, &self.entries, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<ClipboardEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&ClipboardEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClipboardEntry, ClipboardHistoryMode};
+    use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+    use crate::models::application::ClipboardContent;
+
+    #[test]
+    fn search_lists_every_entry_without_a_query() {
+        let entries = vec![
+            ClipboardEntry::new(ClipboardContent::Block("nexedit\neditor\n".to_string())).unwrap(),
+            ClipboardEntry::new(ClipboardContent::Inline("ring".to_string())).unwrap(),
+        ];
+        let mut mode = ClipboardHistoryMode::new(entries, SearchSelectConfig::default());
+        mode.search();
+
+        let results: Vec<String> = mode.results().map(|entry| entry.to_string()).collect();
+        assert_eq!(results, vec!["nexedit (+1 lines)", "ring"]);
+
+        mode.query().push_str("ring");
+        mode.search();
+
+        assert_eq!(
+            mode.selection().map(|entry| &entry.content),
+            Some(&ClipboardContent::Inline("ring".to_string()))
+        );
+    }
+}
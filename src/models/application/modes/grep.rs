@@ -0,0 +1,421 @@
+use crate::models::application::modes::{SearchCase, SearchSelectConfig, SearchSelectMode};
+use crate::models::application::Event;
+use crate::util::SelectableVec;
+use bloodhound::ExclusionPattern;
+use regex::{Regex, RegexBuilder};
+use scribe::buffer::Position;
+use std::cmp;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::slice::Iter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Searches wait this long before starting, so that
+// typing a query doesn't search for each prefix of it.
+const SEARCH_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrepResult {
+    pub path: PathBuf,
+    pub position: Position,
+    pub snippet: String,
+}
+
+impl fmt::Display for GrepResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.path.to_string_lossy(),
+            self.position.line + 1,
+            self.snippet
+        )
+    }
+}
+
+pub struct GrepMode {
+    pub insert: bool,
+    pub input: String,
+    pub results: SelectableVec<GrepResult>,
+    path: PathBuf,
+    paths: Option<Arc<Vec<PathBuf>>>,
+    case: SearchCase,
+    events: Sender<Event>,
+    search_id: Arc<AtomicUsize>,
+    searching: bool,
+    config: SearchSelectConfig,
+}
+
+impl GrepMode {
+    pub fn new(
+        path: PathBuf,
+        exclusions: Option<Vec<ExclusionPattern>>,
+        case: SearchCase,
+        events: Sender<Event>,
+        config: SearchSelectConfig,
+    ) -> GrepMode {
+        // The workspace is indexed once, rather than for every search.
+        let index_path = path.clone();
+        let index_events = events.clone();
+        thread::spawn(move || {
+            let mut paths = Vec::new();
+            collect_paths(&index_path, exclusions.as_deref(), &mut paths);
+            let _ = index_events.send(Event::GrepModeIndexComplete(paths));
+        });
+
+        GrepMode {
+            insert: true,
+            input: String::new(),
+            results: SelectableVec::new(Vec::new()),
+            path,
+            paths: None,
+            case,
+            events,
+            search_id: Arc::new(AtomicUsize::new(0)),
+            searching: false,
+            config,
+        }
+    }
+
+    pub fn set_index(&mut self, paths: Vec<PathBuf>) {
+        self.paths = Some(Arc::new(paths));
+    }
+
+    pub fn add_results(&mut self, search_id: usize, results: Vec<GrepResult>, complete: bool) {
+        if search_id != self.search_id.load(Ordering::SeqCst) {
+            return;
+        }
+
+        self.results.extend(results);
+        if complete {
+            self.searching = false;
+        }
+    }
+
+    // Results are streamed in, so the picker shows a
+    // window of them that follows the selection.
+    fn window_start(&self) -> usize {
+        let selected_index = self.results.selected_index();
+
+        if selected_index < self.config.max_results {
+            0
+        } else {
+            selected_index + 1 - self.config.max_results
+        }
+    }
+}
+
+impl fmt::Display for GrepMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GREP")
+    }
+}
+
+impl Drop for GrepMode {
+    fn drop(&mut self) {
+        // Stop any in-progress search.
+        self.search_id.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl SearchSelectMode<GrepResult> for GrepMode {
+    fn search(&mut self) {
+        let search_id = self.search_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.results = SelectableVec::new(Vec::new());
+        self.searching = false;
+
+        let paths = match self.paths {
+            Some(ref paths) if !self.input.is_empty() => paths.clone(),
+            _ => return,
+        };
+
+        let pattern = match RegexBuilder::new(&regex::escape(&self.input))
+            .case_insensitive(self.case.ignores_case_for(&self.input))
+            .build()
+        {
+            Ok(pattern) => pattern,
+            Err(_) => return,
+        };
+        let path = self.path.clone();
+        let current_id = self.search_id.clone();
+        let events = self.events.clone();
+        self.searching = true;
+
+        thread::spawn(move || {
+            thread::sleep(SEARCH_DELAY);
+
+            for file_path in paths.iter() {
+                if current_id.load(Ordering::SeqCst) != search_id {
+                    return;
+                }
+
+                let results = grep_file(&path, file_path, &pattern);
+                if !results.is_empty() {
+                    let _ = events.send(Event::GrepModeResults {
+                        search_id,
+                        results,
+                        complete: false,
+                    });
+                }
+            }
+
+            let _ = events.send(Event::GrepModeResults {
+                search_id,
+                results: Vec::new(),
+                complete: true,
+            });
+        });
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<GrepResult> {
+        let start = self.window_start();
+        let end = cmp::min(start + self.config.max_results, self.results.len());
+
+        self.results[start..end].iter()
+    }
+
+    fn selection(&self) -> Option<&GrepResult> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index() - self.window_start()
+    }
+
+    fn select_previous(&mut self) {
+        if !self.results.is_empty() {
+            self.results.select_previous();
+        }
+    }
+
+    fn select_next(&mut self) {
+        if !self.results.is_empty() {
+            self.results.select_next();
+        }
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.paths.is_none() {
+            Some(format!("Indexing {}", self.path.to_string_lossy()))
+        } else if self.query().is_empty() {
+            Some(String::from("Enter a search query to start."))
+        } else if self.results.is_empty() && self.searching {
+            Some(format!("Searching {}", self.path.to_string_lossy()))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
+
+fn collect_paths(path: &Path, exclusions: Option<&[ExclusionPattern]>, paths: &mut Vec<PathBuf>) {
+    let mut entries: Vec<_> = match fs::read_dir(path) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+        Err(_) => return,
+    };
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let entry_path = entry.path();
+        let excluded = exclusions
+            .map(|exclusions| {
+                exclusions
+                    .iter()
+                    .any(|exclusion| exclusion.matches(&entry_path.to_string_lossy()))
+            })
+            .unwrap_or(false);
+        if excluded {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => collect_paths(&entry_path, exclusions, paths),
+            Ok(file_type) if file_type.is_file() => paths.push(entry_path),
+            _ => (),
+        }
+    }
+}
+
+fn grep_file(root: &Path, path: &Path, pattern: &Regex) -> Vec<GrepResult> {
+    // Skip anything that isn't valid UTF-8 (e.g. binaries).
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let relative_path = path.strip_prefix(root).unwrap_or(path);
+
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line, data)| {
+            pattern.find(data).map(|found| GrepResult {
+                path: relative_path.to_path_buf(),
+                position: Position {
+                    line,
+                    offset: data[..found.start()].graphemes(true).count(),
+                },
+                snippet: data.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{grep_file, GrepMode, GrepResult};
+    use crate::models::application::modes::{SearchCase, SearchSelectConfig, SearchSelectMode};
+    use crate::models::application::Event;
+    use regex::Regex;
+    use scribe::buffer::Position;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn result(line: usize) -> GrepResult {
+        GrepResult {
+            path: PathBuf::from("src/lib.rs"),
+            position: Position { line, offset: 0 },
+            snippet: String::from("content"),
+        }
+    }
+
+    #[test]
+    fn grep_file_returns_relative_paths_and_grapheme_positions() {
+        let root = env::temp_dir();
+        let name = format!("nexedit_grep_file_{}.txt", process::id());
+        let path = root.join(&name);
+        fs::write(&path, "first\n  ƒn needle()\nneedle").unwrap();
+
+        let results = grep_file(&root, &path, &Regex::new("needle").unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            results,
+            vec![
+                GrepResult {
+                    path: PathBuf::from(&name),
+                    position: Position { line: 1, offset: 5 },
+                    snippet: String::from("ƒn needle()"),
+                },
+                GrepResult {
+                    path: PathBuf::from(&name),
+                    position: Position { line: 2, offset: 0 },
+                    snippet: String::from("needle"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn search_uses_the_indexed_paths() {
+        let root = env::temp_dir().join(format!("nexedit_grep_index_{}", process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("indexed.txt"), "needle").unwrap();
+        let (events, receiver) = mpsc::channel();
+        let mut mode = GrepMode::new(
+            root.clone(),
+            None,
+            SearchCase::Sensitive,
+            events,
+            SearchSelectConfig::default(),
+        );
+        mode.set_index(vec![root.join("indexed.txt")]);
+
+        // Files added after indexing aren't searched.
+        fs::write(root.join("unindexed.txt"), "needle").unwrap();
+        mode.input = String::from("needle");
+        mode.search();
+        let mut results = Vec::new();
+        while let Ok(event) = receiver.recv_timeout(Duration::from_secs(5)) {
+            if let Event::GrepModeResults {
+                results: found,
+                complete,
+                ..
+            } = event
+            {
+                results.extend(found);
+                if complete {
+                    break;
+                }
+            }
+        }
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.path.clone())
+                .collect::<Vec<PathBuf>>(),
+            vec![PathBuf::from("indexed.txt")]
+        );
+    }
+
+    #[test]
+    fn display_includes_path_line_number_and_snippet() {
+        assert_eq!(result(2).to_string(), "src/lib.rs:3: content");
+    }
+
+    #[test]
+    fn add_results_ignores_stale_searches() {
+        let (events, _) = mpsc::channel();
+        let mut mode = GrepMode::new(
+            env::temp_dir(),
+            None,
+            SearchCase::Sensitive,
+            events,
+            SearchSelectConfig::default(),
+        );
+
+        mode.add_results(1, vec![result(0)], true);
+
+        assert!(mode.results.is_empty());
+    }
+
+    #[test]
+    fn results_follow_the_selection() {
+        let (events, _) = mpsc::channel();
+        let mut mode = GrepMode::new(
+            env::temp_dir(),
+            None,
+            SearchCase::Sensitive,
+            events,
+            SearchSelectConfig { max_results: 2 },
+        );
+        mode.add_results(0, vec![result(0), result(1), result(2)], true);
+
+        mode.select_next();
+        mode.select_next();
+
+        assert_eq!(
+            mode.results().map(|r| r.position.line).collect::<Vec<usize>>(),
+            vec![1, 2]
+        );
+        assert_eq!(mode.selected_index(), 1);
+    }
+}
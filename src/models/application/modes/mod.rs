@@ -1,27 +1,41 @@
+mod buffer_list;
+mod clipboard_history;
 mod command;
 mod confirm;
+mod grep;
 pub mod jump;
 mod line_jump;
 pub mod open;
 mod path;
+mod recent_files;
+mod replace;
 mod search;
 mod search_select;
 mod select;
+pub mod select_block;
 mod select_line;
+mod surround;
 mod symbol_jump;
 mod syntax;
 mod theme;
 
+pub use self::buffer_list::{BufferEntry, BufferListMode};
+pub use self::clipboard_history::{ClipboardEntry, ClipboardHistoryMode};
 pub use self::command::CommandMode;
 pub use self::confirm::ConfirmMode;
+pub use self::grep::{GrepMode, GrepResult};
 pub use self::jump::JumpMode;
 pub use self::line_jump::LineJumpMode;
 pub use self::open::OpenMode;
 pub use self::path::PathMode;
-pub use self::search::SearchMode;
+pub use self::recent_files::RecentFilesMode;
+pub use self::replace::ReplaceMode;
+pub use self::search::{SearchCase, SearchMode};
 pub use self::search_select::{SearchSelectConfig, SearchSelectMode};
 pub use self::select::SelectMode;
+pub use self::select_block::SelectBlockMode;
 pub use self::select_line::SelectLineMode;
+pub use self::surround::{SurroundMode, SurroundTarget};
 pub use self::symbol_jump::SymbolJumpMode;
 pub use self::syntax::SyntaxMode;
 pub use self::theme::ThemeMode;
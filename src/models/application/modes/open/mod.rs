@@ -8,11 +8,15 @@ use crate::util::SelectableVec;
 use bloodhound::ExclusionPattern;
 pub use bloodhound::Index;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::slice::Iter;
 use std::sync::mpsc::Sender;
 use std::thread;
 
+// How many times the usual number of matches are considered
+// when lifting recently opened files to the top of the results.
+const RECENT_CANDIDATE_FACTOR: usize = 3;
+
 #[derive(PartialEq)]
 pub enum OpenModeIndex {
     Complete(Index),
@@ -24,14 +28,18 @@ pub struct OpenMode {
     pub input: String,
     index: OpenModeIndex,
     pub results: SelectableVec<DisplayablePath>,
+    recent: Vec<PathBuf>,
     config: SearchSelectConfig,
 }
 
 impl OpenMode {
+    /// Builds the mode, weighting results in favour of the recent paths
+    /// (relative to the workspace, and ordered from most to least recent).
     pub fn new(
         path: PathBuf,
         exclusions: Option<Vec<ExclusionPattern>>,
         events: Sender<Event>,
+        recent: Vec<PathBuf>,
         config: SearchSelectConfig,
     ) -> OpenMode {
         let index_path = path.clone();
@@ -46,6 +54,7 @@ impl OpenMode {
             input: String::new(),
             index: OpenModeIndex::Indexing(path),
             results: SelectableVec::new(Vec::new()),
+            recent,
             config,
         }
     }
@@ -64,8 +73,14 @@ impl fmt::Display for OpenMode {
 impl SearchSelectMode<DisplayablePath> for OpenMode {
     fn search(&mut self) {
         let results = if let OpenModeIndex::Complete(ref index) = self.index {
-            index
-                .find(&self.input.to_lowercase(), self.config.max_results)
+            let limit = if self.recent.is_empty() {
+                self.config.max_results
+            } else {
+                self.config.max_results * RECENT_CANDIDATE_FACTOR
+            };
+            let paths = index.find(&self.input.to_lowercase(), limit);
+
+            prefer_recent(paths, &self.recent, self.config.max_results)
                 .into_iter()
                 .map(|path| DisplayablePath(path.to_path_buf()))
                 .collect()
@@ -124,3 +139,43 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
         }
     }
 }
+
+// Moves recently opened paths ahead of the others, most recent first,
+// leaving the rest in their original order.
+fn prefer_recent<'a>(mut paths: Vec<&'a Path>, recent: &[PathBuf], limit: usize) -> Vec<&'a Path> {
+    paths.sort_by_key(|path| {
+        recent
+            .iter()
+            .position(|recent_path| recent_path == path)
+            .unwrap_or(recent.len())
+    });
+    paths.truncate(limit);
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prefer_recent;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn prefer_recent_lifts_recent_paths_above_closer_matches() {
+        let paths = vec![
+            Path::new("src/main.rs"),
+            Path::new("src/lib.rs"),
+            Path::new("src/models/mod.rs"),
+            Path::new("README.md"),
+        ];
+        let recent = vec![PathBuf::from("src/models/mod.rs"), PathBuf::from("src/lib.rs")];
+
+        assert_eq!(
+            prefer_recent(paths, &recent, 3),
+            vec![
+                Path::new("src/models/mod.rs"),
+                Path::new("src/lib.rs"),
+                Path::new("src/main.rs")
+            ]
+        );
+    }
+}
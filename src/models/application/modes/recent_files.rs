@@ -0,0 +1,117 @@
+use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+use crate::util::SelectableVec;
+use fragment;
+use std::fmt;
+use std::slice::Iter;
+
+pub struct RecentFilesMode {
+    insert: bool,
+    input: String,
+    paths: Vec<String>,
+    results: SelectableVec<String>,
+    config: SearchSelectConfig,
+}
+
+impl RecentFilesMode {
+    /// Builds the mode using paths ordered from most to least recently opened.
+    pub fn new(paths: Vec<String>, config: SearchSelectConfig) -> RecentFilesMode {
+        RecentFilesMode {
+            insert: true,
+            input: String::new(),
+            paths,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for RecentFilesMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RECENT FILES")
+    }
+}
+
+impl SearchSelectMode<String> for RecentFilesMode {
+    fn search(&mut self) {
+        // Without a query, paths are listed from most to least recently opened.
+        let results = if self.input.is_empty() {
+            self.paths
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fragment::matching::find(&self.input, &self.paths, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<String> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&String> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.paths.is_empty() {
+            Some(String::from("No files have been opened yet."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching files found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecentFilesMode;
+    use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+
+    #[test]
+    fn search_lists_every_path_in_order_without_a_query() {
+        let paths = vec![String::from("/tmp/notes.md"), String::from("/src/main.rs")];
+        let mut mode = RecentFilesMode::new(paths.clone(), SearchSelectConfig::default());
+        mode.search();
+
+        assert_eq!(mode.results().cloned().collect::<Vec<String>>(), paths);
+
+        mode.query().push_str("main");
+        mode.search();
+        assert_eq!(mode.selection(), Some(&String::from("/src/main.rs")));
+    }
+}
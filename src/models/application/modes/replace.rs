@@ -0,0 +1,206 @@
+use crate::errors::*;
+use crate::models::application::modes::SearchMode;
+use scribe::buffer::{Buffer, Distance, Position, Range};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub struct ReplaceMode {
+    pub insert: bool,
+    pub input: String,
+    pub search: SearchMode,
+    remaining: usize,
+}
+
+impl ReplaceMode {
+    pub fn new(search: SearchMode) -> ReplaceMode {
+        let remaining = search.results.as_ref().map(|r| r.len()).unwrap_or(0);
+
+        ReplaceMode {
+            insert: true,
+            input: String::new(),
+            search,
+            remaining,
+        }
+    }
+
+    pub fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub fn current_result(&self) -> Option<&Range> {
+        self.search.results.as_ref().and_then(|r| r.selection())
+    }
+
+    pub fn replace_current(&mut self, buffer: &mut Buffer) -> Result<()> {
+        let range = self.current_result().ok_or(NO_SEARCH_RESULTS)?.clone();
+        let content = self
+            .search
+            .replacement_for(&range, &buffer.data(), &self.input)?;
+
+        buffer.delete_range(range.clone());
+        buffer.cursor.move_to(range.start());
+        buffer.insert(content.clone());
+
+        let replaced_end = range.start() + distance_of(&content);
+        let results = self.search.results.as_mut().ok_or(NO_SEARCH_RESULTS)?;
+        results.remove_selection();
+        for result in results.iter_mut() {
+            if result.start() >= range.end() {
+                *result = Range::new(
+                    shift(result.start(), range.end(), replaced_end),
+                    shift(result.end(), range.end(), replaced_end),
+                );
+            }
+        }
+        self.remaining = self.remaining.saturating_sub(1);
+
+        Ok(())
+    }
+
+    pub fn skip(&mut self) -> Result<()> {
+        self.search
+            .results
+            .as_mut()
+            .ok_or(NO_SEARCH_RESULTS)?
+            .select_next();
+        self.remaining = self.remaining.saturating_sub(1);
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReplaceMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REPLACE")
+    }
+}
+
+fn distance_of(content: &str) -> Distance {
+    Distance {
+        lines: content.matches('\n').count(),
+        offset: content
+            .rsplit('\n')
+            .next()
+            .map(|line| line.graphemes(true).count())
+            .unwrap_or(0),
+    }
+}
+
+// Moves a position that followed a replaced range so that
+// it keeps pointing at the same content after the edit.
+fn shift(position: Position, old_end: Position, new_end: Position) -> Position {
+    if position.line == old_end.line {
+        Position {
+            line: new_end.line,
+            offset: new_end.offset + (position.offset - old_end.offset),
+        }
+    } else {
+        Position {
+            line: position.line - old_end.line + new_end.line,
+            offset: position.offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplaceMode;
+    use crate::models::application::modes::{SearchCase, SearchMode};
+    use scribe::buffer::{Buffer, Position, Range};
+
+    fn replace_mode(buffer: &Buffer, query: &str, regex: bool) -> ReplaceMode {
+        let mut search = SearchMode::new(Some(String::from(query)), regex, SearchCase::Sensitive);
+        search.search(buffer).unwrap();
+
+        ReplaceMode::new(search)
+    }
+
+    #[test]
+    fn replace_current_replaces_the_selected_result_and_shifts_the_rest() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ab ab\nab");
+        let mut mode = replace_mode(&buffer, "ab", false);
+        mode.input = String::from("xyz\nw");
+
+        mode.replace_current(&mut buffer).unwrap();
+
+        assert_eq!(buffer.data(), "xyz\nw ab\nab");
+        assert_eq!(mode.remaining(), 2);
+        assert_eq!(
+            *mode.search.results.unwrap(),
+            vec![
+                Range::new(
+                    Position { line: 1, offset: 2 },
+                    Position { line: 1, offset: 4 },
+                ),
+                Range::new(
+                    Position { line: 2, offset: 0 },
+                    Position { line: 2, offset: 2 },
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_current_does_not_revisit_replacement_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a a");
+        let mut mode = replace_mode(&buffer, "a", false);
+        mode.input = String::from("aa");
+
+        while !mode.is_complete() {
+            mode.replace_current(&mut buffer).unwrap();
+        }
+
+        assert_eq!(buffer.data(), "aa aa");
+    }
+
+    #[test]
+    fn replace_current_expands_capture_references_in_regex_mode() {
+        let mut buffer = Buffer::new();
+        buffer.insert("x = 1; y = 2;");
+        let mut mode = replace_mode(&buffer, r"(\w) = (\d)", true);
+        mode.input = String::from("$1 == $2");
+
+        mode.replace_current(&mut buffer).unwrap();
+        mode.replace_current(&mut buffer).unwrap();
+
+        assert_eq!(buffer.data(), "x == 1; y == 2;");
+        assert!(mode.is_complete());
+    }
+
+    #[test]
+    fn skip_moves_to_the_next_result_without_changing_the_buffer() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ab ab");
+        let mut mode = replace_mode(&buffer, "ab", false);
+        mode.input = String::from("x");
+
+        mode.skip().unwrap();
+        mode.replace_current(&mut buffer).unwrap();
+
+        assert_eq!(buffer.data(), "ab x");
+        assert!(mode.is_complete());
+    }
+
+    #[test]
+    fn skip_stops_counting_down_once_complete() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ab");
+        let mut mode = replace_mode(&buffer, "ab", false);
+
+        mode.skip().unwrap();
+        mode.skip().unwrap();
+
+        assert_eq!(mode.remaining(), 0);
+        assert!(mode.is_complete());
+    }
+}
@@ -1,20 +1,63 @@
 use crate::errors::*;
 use crate::util::SelectableVec;
-use scribe::buffer::{Buffer, Distance, Range};
+use regex::{Regex, RegexBuilder};
+use scribe::buffer::{Buffer, Distance, Position, Range};
 use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchCase {
+    Sensitive,
+    Insensitive,
+    Smart,
+}
+
+impl SearchCase {
+    pub fn next(self) -> SearchCase {
+        match self {
+            SearchCase::Sensitive => SearchCase::Insensitive,
+            SearchCase::Insensitive => SearchCase::Smart,
+            SearchCase::Smart => SearchCase::Sensitive,
+        }
+    }
+
+    pub fn ignores_case_for(self, query: &str) -> bool {
+        match self {
+            SearchCase::Sensitive => false,
+            SearchCase::Insensitive => true,
+            SearchCase::Smart => !query.chars().any(char::is_uppercase),
+        }
+    }
+}
+
+impl fmt::Display for SearchCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchCase::Sensitive => write!(f, "case-sensitive"),
+            SearchCase::Insensitive => write!(f, "case-insensitive"),
+            SearchCase::Smart => write!(f, "smart case"),
+        }
+    }
+}
 
 pub struct SearchMode {
     pub insert: bool,
     pub input: Option<String>,
+    pub regex: bool,
+    pub case: SearchCase,
     pub results: Option<SelectableVec<Range>>,
+    expression: Option<Regex>,
 }
 
 impl SearchMode {
-    pub fn new(query: Option<String>) -> SearchMode {
+    pub fn new(query: Option<String>, regex: bool, case: SearchCase) -> SearchMode {
         SearchMode {
             insert: true,
             input: query,
+            regex,
+            case,
             results: None,
+            expression: None,
         }
     }
 
@@ -24,29 +67,118 @@ impl SearchMode {
 
     pub fn search(&mut self, buffer: &Buffer) -> Result<()> {
         let query = self.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
-        let distance = Distance::of_str(query);
 
-        self.results = Some(SelectableVec::new(
+        let ignore_case = self.case.ignores_case_for(query);
+
+        // Regular expressions are kept to expand replacements with.
+        self.expression = if self.regex {
+            Some(compile_regex(query, ignore_case)?)
+        } else {
+            None
+        };
+
+        let results = if let Some(ref expression) = self.expression {
+            regex_search(expression, &buffer.data())
+        } else if ignore_case {
+            let expression = compile_regex(&regex::escape(query), ignore_case)?;
+            regex_search(&expression, &buffer.data())
+        } else {
+            let distance = Distance::of_str(query);
+
             buffer
                 .search(query)
                 .into_iter()
                 .map(|start| Range::new(start, start + distance))
-                .collect(),
-        ));
+                .collect()
+        };
+        self.results = Some(SelectableVec::new(results));
 
         Ok(())
     }
+
+    pub fn replacement_for(&self, range: &Range, data: &str, template: &str) -> Result<String> {
+        let expression = match self.expression {
+            Some(ref expression) => expression,
+            None => return Ok(template.to_string()),
+        };
+        let start = byte_offset(data, &range.start()).ok_or("Search result is out of bounds")?;
+        let captures = expression
+            .captures_at(data, start)
+            .filter(|captures| captures.get(0).map(|m| m.start()) == Some(start))
+            .ok_or("Search result no longer matches the query")?;
+
+        let mut replacement = String::new();
+        captures.expand(template, &mut replacement);
+
+        Ok(replacement)
+    }
 }
 
 impl fmt::Display for SearchMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "SEARCH")
+        if self.regex {
+            write!(f, "REGEX SEARCH")
+        } else {
+            write!(f, "SEARCH")
+        }
+    }
+}
+
+fn compile_regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .chain_err(|| format!("Invalid regular expression: {}", pattern))
+}
+
+fn regex_search(expression: &Regex, data: &str) -> Vec<Range> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(data.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+
+    expression
+        .find_iter(data)
+        .filter(|m| !m.is_empty())
+        .map(|m| {
+            Range::new(
+                byte_position(data, &line_starts, m.start()),
+                byte_position(data, &line_starts, m.end()),
+            )
+        })
+        .collect()
+}
+
+// Regex matches are reported as byte offsets, whereas buffer
+// positions use grapheme offsets relative to the start of a line.
+fn byte_position(data: &str, line_starts: &[usize], byte_offset: usize) -> Position {
+    let line = line_starts.partition_point(|&start| start <= byte_offset) - 1;
+
+    Position {
+        line,
+        offset: data[line_starts[line]..byte_offset].graphemes(true).count(),
+    }
+}
+
+fn byte_offset(data: &str, position: &Position) -> Option<usize> {
+    let mut line_start = 0;
+    for line in data.split_inclusive('\n').take(position.line) {
+        line_start += line.len();
+    }
+
+    let line = data[line_start..].split('\n').next()?;
+    if position.offset == 0 {
+        return Some(line_start);
     }
+
+    line.grapheme_indices(true)
+        .map(|(index, grapheme)| index + grapheme.len())
+        .nth(position.offset - 1)
+        .map(|index| line_start + index)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SearchMode;
+    use super::{SearchCase, SearchMode};
     use scribe::buffer::{Buffer, Position, Range};
 
     #[test]
@@ -54,7 +186,7 @@ mod tests {
         let mut buffer = Buffer::new();
         buffer.insert("test\ntest");
 
-        let mut mode = SearchMode::new(Some(String::from("test")));
+        let mut mode = SearchMode::new(Some(String::from("test")), false, SearchCase::Sensitive);
         mode.search(&buffer).unwrap();
 
         assert_eq!(
@@ -71,4 +203,213 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn search_treats_query_literally_when_regex_is_disabled() {
+        let mut buffer = Buffer::new();
+        buffer.insert("t.st\ntest");
+
+        let mut mode = SearchMode::new(Some(String::from("t.st")), false, SearchCase::Sensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(
+                Position { line: 0, offset: 0 },
+                Position { line: 0, offset: 4 },
+            )]
+        );
+    }
+
+    #[test]
+    fn search_populates_results_with_regex_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("let a = 12;\nlet b = 345;");
+
+        let mut mode = SearchMode::new(Some(String::from(r"\d+")), true, SearchCase::Sensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![
+                Range::new(
+                    Position { line: 0, offset: 8 },
+                    Position {
+                        line: 0,
+                        offset: 10
+                    },
+                ),
+                Range::new(
+                    Position { line: 1, offset: 8 },
+                    Position {
+                        line: 1,
+                        offset: 11
+                    },
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_populates_results_with_multi_line_regex_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("first\nsecond\nthird");
+
+        let mut mode = SearchMode::new(Some(String::from(r"st\nsec")), true, SearchCase::Sensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(
+                Position { line: 0, offset: 3 },
+                Position { line: 1, offset: 3 },
+            )]
+        );
+    }
+
+    #[test]
+    fn search_uses_grapheme_offsets_for_regex_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("éé test");
+
+        let mut mode = SearchMode::new(Some(String::from("te")), true, SearchCase::Sensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(
+                Position { line: 0, offset: 3 },
+                Position { line: 0, offset: 5 },
+            )]
+        );
+    }
+
+    #[test]
+    fn search_skips_empty_regex_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("abc");
+
+        let mut mode = SearchMode::new(Some(String::from("x*")), true, SearchCase::Sensitive);
+        mode.search(&buffer).unwrap();
+
+        assert!(mode.results.unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_returns_an_error_for_invalid_regex() {
+        let mut buffer = Buffer::new();
+        buffer.insert("abc");
+
+        let mut mode = SearchMode::new(Some(String::from("(")), true, SearchCase::Sensitive);
+
+        assert!(mode.search(&buffer).is_err());
+    }
+
+    #[test]
+    fn search_ignores_case_when_case_insensitive() {
+        let mut buffer = Buffer::new();
+        buffer.insert("Test\ntest");
+
+        let mut mode = SearchMode::new(Some(String::from("TEST")), false, SearchCase::Insensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(mode.results.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn search_escapes_literal_queries_when_case_insensitive() {
+        let mut buffer = Buffer::new();
+        buffer.insert("A.b\naxb");
+
+        let mut mode = SearchMode::new(Some(String::from("a.b")), false, SearchCase::Insensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(
+                Position { line: 0, offset: 0 },
+                Position { line: 0, offset: 3 },
+            )]
+        );
+    }
+
+    #[test]
+    fn search_ignores_case_for_regex_matches_when_case_insensitive() {
+        let mut buffer = Buffer::new();
+        buffer.insert("FOO1 foo2");
+
+        let mut mode = SearchMode::new(Some(String::from(r"foo\d")), true, SearchCase::Insensitive);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(mode.results.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn smart_case_search_ignores_case_for_lowercase_queries() {
+        let mut buffer = Buffer::new();
+        buffer.insert("Test\ntest");
+
+        let mut mode = SearchMode::new(Some(String::from("test")), false, SearchCase::Smart);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(mode.results.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn smart_case_search_respects_case_for_queries_with_uppercase_characters() {
+        let mut buffer = Buffer::new();
+        buffer.insert("Test\ntest");
+
+        let mut mode = SearchMode::new(Some(String::from("Test")), false, SearchCase::Smart);
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(
+                Position { line: 0, offset: 0 },
+                Position { line: 0, offset: 4 },
+            )]
+        );
+    }
+
+    #[test]
+    fn replacement_for_returns_template_verbatim_for_literal_searches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("foo");
+
+        let mut mode = SearchMode::new(Some(String::from("foo")), false, SearchCase::Sensitive);
+        mode.search(&buffer).unwrap();
+        let range = mode.results.as_ref().unwrap()[0].clone();
+
+        assert_eq!(
+            mode.replacement_for(&range, &buffer.data(), "$1bar")
+                .unwrap(),
+            "$1bar"
+        );
+    }
+
+    #[test]
+    fn replacement_for_expands_regex_capture_references() {
+        let mut buffer = Buffer::new();
+        buffer.insert("é x = 1;\nlet y = 2;");
+
+        let mut mode = SearchMode::new(
+            Some(String::from(r"(\w+) = (\d)")),
+            true,
+            SearchCase::Sensitive,
+        );
+        mode.search(&buffer).unwrap();
+        let ranges = mode.results.as_ref().unwrap().to_vec();
+
+        assert_eq!(
+            mode.replacement_for(&ranges[0], &buffer.data(), "$2 = $1")
+                .unwrap(),
+            "1 = x"
+        );
+        assert_eq!(
+            mode.replacement_for(&ranges[1], &buffer.data(), "${2}0")
+                .unwrap(),
+            "20"
+        );
+    }
 }
@@ -0,0 +1,120 @@
+use scribe::buffer::{Position, Range};
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub struct SelectBlockMode {
+    pub anchor: Position,
+}
+
+impl SelectBlockMode {
+    pub fn new(anchor: Position) -> SelectBlockMode {
+        SelectBlockMode { anchor }
+    }
+
+    /// Returns one range per line in the block, between the screen
+    /// columns of the anchor and cursor. Lines that end before the block
+    /// produce an empty range at the end of the line.
+    pub fn to_ranges(&self, data: &str, cursor: &Position, tab_width: usize) -> Vec<Range> {
+        let first_line = cmp::min(self.anchor.line, cursor.line);
+        let last_line = cmp::max(self.anchor.line, cursor.line);
+        let lines: Vec<&str> = data.split('\n').collect();
+        let anchor_column = lines
+            .get(self.anchor.line)
+            .map(|line| column_of(line, self.anchor.offset, tab_width))
+            .unwrap_or(0);
+        let cursor_column = lines
+            .get(cursor.line)
+            .map(|line| column_of(line, cursor.offset, tab_width))
+            .unwrap_or(0);
+        let start_column = cmp::min(anchor_column, cursor_column);
+        let end_column = cmp::max(anchor_column, cursor_column);
+
+        lines
+            .iter()
+            .enumerate()
+            .skip(first_line)
+            .take(last_line + 1 - first_line)
+            .map(|(line, content)| {
+                Range::new(
+                    Position {
+                        line,
+                        offset: offset_of(content, start_column, tab_width),
+                    },
+                    Position {
+                        line,
+                        offset: offset_of(content, end_column, tab_width),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Screen column of the grapheme at the given offset, expanding tabs
+/// to the next tab stop the same way the buffer renderer does.
+pub fn column_of(line: &str, offset: usize, tab_width: usize) -> usize {
+    line.graphemes(true)
+        .take(offset)
+        .fold(0, |column, grapheme| advance(column, grapheme, tab_width))
+}
+
+/// Grapheme offset of the first character starting at or after the
+/// given screen column, clamped to the end of the line.
+pub fn offset_of(line: &str, column: usize, tab_width: usize) -> usize {
+    let mut current_column = 0;
+
+    for (offset, grapheme) in line.graphemes(true).enumerate() {
+        if current_column >= column {
+            return offset;
+        }
+        current_column = advance(current_column, grapheme, tab_width);
+    }
+
+    line.graphemes(true).count()
+}
+
+fn advance(column: usize, grapheme: &str, tab_width: usize) -> usize {
+    if grapheme == "\t" && tab_width > 0 {
+        (column / tab_width + 1) * tab_width
+    } else {
+        column + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{column_of, offset_of, SelectBlockMode};
+    use scribe::buffer::{Position, Range};
+
+    #[test]
+    fn column_of_expands_tabs_to_the_next_tab_stop() {
+        assert_eq!(column_of("a\tb", 2, 4), 4);
+        assert_eq!(column_of("ƒn\tb", 3, 4), 4);
+        assert_eq!(offset_of("a\tb", 4, 4), 2);
+        assert_eq!(offset_of("a\tb", 10, 4), 3);
+    }
+
+    #[test]
+    fn to_ranges_selects_the_same_columns_on_every_line() {
+        let mode = SelectBlockMode::new(Position { line: 2, offset: 1 });
+        let ranges = mode.to_ranges("abcdef\n\tbc\nab", &Position { line: 0, offset: 5 }, 4);
+
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new(
+                    Position { line: 0, offset: 1 },
+                    Position { line: 0, offset: 5 }
+                ),
+                Range::new(
+                    Position { line: 1, offset: 1 },
+                    Position { line: 1, offset: 2 }
+                ),
+                Range::new(
+                    Position { line: 2, offset: 1 },
+                    Position { line: 2, offset: 2 }
+                ),
+            ]
+        );
+    }
+}
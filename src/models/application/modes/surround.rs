@@ -0,0 +1,29 @@
+use crate::util::surround::Surrounding;
+use scribe::buffer::Range;
+use std::fmt;
+
+/// What a tag entered in surround mode is applied to.
+pub enum SurroundTarget {
+    Selection(Range),
+    Surrounding(Surrounding),
+}
+
+pub struct SurroundMode {
+    pub input: String,
+    pub target: SurroundTarget,
+}
+
+impl SurroundMode {
+    pub fn new(target: SurroundTarget) -> SurroundMode {
+        SurroundMode {
+            input: String::new(),
+            target,
+        }
+    }
+}
+
+impl fmt::Display for SurroundMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TAG")
+    }
+}
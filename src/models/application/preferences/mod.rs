@@ -1,29 +1,52 @@
 use crate::errors::*;
-use crate::input::KeyMap;
+use crate::input::{parse_key_sequence, Key, KeyMap};
 use crate::models::application::modes::open;
-use crate::models::application::modes::SearchSelectConfig;
+use crate::models::application::ClipboardBackend;
+use crate::models::application::modes::{SearchCase, SearchSelectConfig};
 use app_dirs2::{app_dir, app_root, get_app_root, AppDataType, AppInfo};
 use bloodhound::ExclusionPattern;
+use regex::Regex;
 use scribe::Buffer;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Read;
-use std::path::{Path, PathBuf};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
 use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+use syntect::highlighting::ScopeSelectors;
 use yaml_rust::yaml::{Hash, Yaml, YamlLoader};
 
 const APP_INFO: AppInfo = AppInfo {
     name: "nexedit",
     author: "d3vboi",
 };
+const AUTO_PAIR_KEY: &str = "auto_pair";
+const CLIPBOARD_KEY: &str = "clipboard";
 const FILE_NAME: &str = "config.yml";
+const HISTORY_PATH: &str = "history";
+const INDENT_KEY: &str = "indent";
+const INDENT_DECREASE_KEY: &str = "decrease";
+const INDENT_INCREASE_KEY: &str = "increase";
+const FOLD_SCOPE_KEY: &str = "fold_scope";
 const FORMAT_TOOL_KEY: &str = "format_tool";
+const KEY_SEQUENCE_TIMEOUT_KEY: &str = "key_sequence_timeout";
 const LINE_COMMENT_PREFIX_KEY: &str = "line_comment_prefix";
 const LINE_LENGTH_GUIDE_KEY: &str = "line_length_guide";
 const LINE_WRAPPING_KEY: &str = "line_wrapping";
+const MACROS_KEY: &str = "macros";
 const OPEN_MODE_KEY: &str = "open_mode";
 const OPEN_MODE_EXCLUSIONS_KEY: &str = "exclusions";
+const OPEN_MODE_RECENCY_KEY: &str = "recency";
+const SEARCH_KEY: &str = "search";
+const SEARCH_CASE_KEY: &str = "case";
+const SEARCH_HIGHLIGHT_KEY: &str = "highlight";
 const SEARCH_SELECT_KEY: &str = "search_select";
+const SESSION_KEY: &str = "session";
+const SESSION_PATH: &str = "sessions";
 const SOFT_TABS_KEY: &str = "soft_tabs";
+const SURROUND_PAIRS_KEY: &str = "surround_pairs";
 const SYNTAX_PATH: &str = "syntaxes";
 const TAB_WIDTH_KEY: &str = "tab_width";
 const THEME_KEY: &str = "theme";
@@ -36,8 +59,14 @@ pub struct Preferences {
     data: Option<Yaml>,
     keymap: KeyMap,
     theme: Option<String>,
+    search_case: Option<SearchCase>,
+    indent_patterns: RefCell<HashMap<(Option<String>, &'static str), IndentPattern>>,
 }
 
+// Compiled patterns are kept, along with any error, so that
+// they don't have to be compiled again for every key typed.
+type IndentPattern = std::result::Result<Option<Regex>, String>;
+
 impl Preferences {
     pub fn new(data: Option<Yaml>) -> Preferences {
         Preferences {
@@ -45,6 +74,8 @@ impl Preferences {
             data,
             keymap: KeyMap::default().expect("Failed to load default keymap!"),
             theme: None,
+            search_case: None,
+            indent_patterns: RefCell::new(HashMap::new()),
         }
     }
 
@@ -58,6 +89,8 @@ impl Preferences {
             data,
             keymap,
             theme: None,
+            search_case: None,
+            indent_patterns: RefCell::new(HashMap::new()),
         })
     }
 
@@ -70,6 +103,8 @@ impl Preferences {
         self.data = data;
         self.keymap = keymap;
         self.theme = None;
+        self.search_case = None;
+        self.indent_patterns.borrow_mut().clear();
 
         Ok(())
     }
@@ -88,6 +123,20 @@ impl Preferences {
             .chain_err(|| "Couldn't create syntax directory or build a path to it.")
     }
 
+    pub fn history_path() -> Result<PathBuf> {
+        app_dir(AppDataType::UserData, &APP_INFO, HISTORY_PATH)
+            .chain_err(|| "Couldn't create history directory or build a path to it.")
+    }
+
+    /// Where the session for the specified workspace directory is kept.
+    pub fn session_path(workspace: &Path) -> Result<PathBuf> {
+        let name = workspace.to_string_lossy().replace(MAIN_SEPARATOR, "%");
+
+        app_dir(AppDataType::UserData, &APP_INFO, SESSION_PATH)
+            .map(|path| path.join(format!("{}.yml", name)))
+            .chain_err(|| "Couldn't create session directory or build a path to it.")
+    }
+
     pub fn edit() -> Result<Buffer> {
         let mut config_path = app_root(AppDataType::UserConfig, &APP_INFO)
             .chain_err(|| "Couldn't create or open application config directory")?;
@@ -121,6 +170,11 @@ impl Preferences {
             })
     }
 
+    /// The theme picked while running, if any, rather than the configured one.
+    pub fn theme_override(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
     pub fn theme_path(&self) -> Result<PathBuf> {
         app_dir(AppDataType::UserConfig, &APP_INFO, THEME_PATH)
             .chain_err(|| "Couldn't create themes directory or build a path to it.")
@@ -163,6 +217,70 @@ impl Preferences {
         result
     }
 
+    pub fn search_case(&self) -> SearchCase {
+        if let Some(case) = self.search_case {
+            return case;
+        }
+
+        self.data
+            .as_ref()
+            .and_then(|data| parse_search_case(&data[SEARCH_KEY][SEARCH_CASE_KEY]))
+            .or_else(|| parse_search_case(&self.default[SEARCH_KEY][SEARCH_CASE_KEY]))
+            .unwrap_or(SearchCase::Sensitive)
+    }
+
+    pub fn set_search_case(&mut self, case: SearchCase) {
+        self.search_case = Some(case);
+    }
+
+    pub fn clipboard(&self) -> ClipboardBackend {
+        self.data
+            .as_ref()
+            .and_then(|data| parse_clipboard_backend(&data[CLIPBOARD_KEY]))
+            .or_else(|| parse_clipboard_backend(&self.default[CLIPBOARD_KEY]))
+            .unwrap_or(ClipboardBackend::System)
+    }
+
+    pub fn key_sequence_timeout(&self) -> Duration {
+        let timeout = self
+            .data
+            .as_ref()
+            .and_then(|data| data[KEY_SEQUENCE_TIMEOUT_KEY].as_i64())
+            .or_else(|| self.default[KEY_SEQUENCE_TIMEOUT_KEY].as_i64())
+            .unwrap_or(1000);
+
+        Duration::from_millis(timeout.max(0) as u64)
+    }
+
+    /// The keys making up a macro defined in the preferences,
+    /// written using the same key names as the keymap.
+    pub fn macro_keys(&self, name: &str) -> Result<Vec<Key>> {
+        let keys = self
+            .data
+            .as_ref()
+            .and_then(|data| data[MACROS_KEY][name].as_str())
+            .ok_or_else(|| format!("Macro \"{}\" doesn't exist", name))?;
+
+        parse_key_sequence(keys).chain_err(|| format!("Failed to parse macro \"{}\"", name))
+    }
+
+    pub fn search_highlight(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[SEARCH_KEY][SEARCH_HIGHLIGHT_KEY].as_bool())
+            .or_else(|| self.default[SEARCH_KEY][SEARCH_HIGHLIGHT_KEY].as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Whether sessions are saved when exiting and restored on startup.
+    pub fn session(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[SESSION_KEY].as_bool())
+            .or_else(|| self.default[SESSION_KEY].as_bool())
+            .unwrap_or(false)
+    }
+
     pub fn soft_tabs(&self, path: Option<&PathBuf>) -> bool {
         self.data
             .as_ref()
@@ -186,6 +304,25 @@ impl Preferences {
             })
     }
 
+    pub fn auto_pair(&self, path: Option<&PathBuf>) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| {
+                if let Some(extension) = path_extension(path) {
+                    if let Yaml::Boolean(auto_pair) = data[TYPES_KEY][extension][AUTO_PAIR_KEY] {
+                        return Some(auto_pair);
+                    }
+                }
+
+                data[AUTO_PAIR_KEY].as_bool()
+            })
+            .unwrap_or_else(|| {
+                self.default[AUTO_PAIR_KEY]
+                    .as_bool()
+                    .expect("Couldn't find default auto pair setting!")
+            })
+    }
+
     pub fn line_length_guides(&self) -> Vec<usize> {
         self.data
             .as_ref()
@@ -255,6 +392,47 @@ impl Preferences {
         }
     }
 
+    /// Whether open mode lists recently opened files ahead of other matches.
+    pub fn open_mode_recency(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[OPEN_MODE_KEY][OPEN_MODE_RECENCY_KEY].as_bool())
+            .or_else(|| self.default[OPEN_MODE_KEY][OPEN_MODE_RECENCY_KEY].as_bool())
+            .unwrap_or(false)
+    }
+
+    /// The pattern matching lines after which indentation increases.
+    pub fn increase_indent_pattern(&self, path: Option<&PathBuf>) -> Result<Option<Regex>> {
+        self.indent_pattern(path, INDENT_INCREASE_KEY)
+    }
+
+    /// The pattern matching lines that are outdented once they're typed.
+    pub fn decrease_indent_pattern(&self, path: Option<&PathBuf>) -> Result<Option<Regex>> {
+        self.indent_pattern(path, INDENT_DECREASE_KEY)
+    }
+
+    /// The scopes of syntax regions, such as functions, that can be folded.
+    pub fn fold_scope(&self, path: Option<&PathBuf>) -> Result<Option<ScopeSelectors>> {
+        let extension = path_extension(path);
+        let scope = self
+            .data
+            .iter()
+            .chain(Some(&self.default))
+            .flat_map(|data| {
+                let type_scope = extension.map(|ext| &data[TYPES_KEY][ext][FOLD_SCOPE_KEY]);
+
+                type_scope.into_iter().chain(Some(&data[FOLD_SCOPE_KEY]))
+            })
+            .find_map(|scope| scope.as_str());
+
+        match scope {
+            Some(scope) if !scope.is_empty() => ScopeSelectors::from_str(scope)
+                .map(Some)
+                .chain_err(|| format!("Invalid fold scope: {}", scope)),
+            _ => Ok(None),
+        }
+    }
+
     pub fn line_comment_prefix(&self, path: &PathBuf) -> Option<String> {
         let extension = path_extension(Some(path))?;
 
@@ -265,6 +443,20 @@ impl Preferences {
             .map(|prefix| prefix.to_owned())
     }
 
+    pub fn surround_pairs(&self, path: Option<&PathBuf>) -> Vec<(String, String)> {
+        let extension = path_extension(path);
+        let documents = self.data.iter().chain(Some(&self.default));
+
+        documents
+            .flat_map(|data| {
+                let type_pairs = extension.map(|ext| &data[TYPES_KEY][ext][SURROUND_PAIRS_KEY]);
+
+                type_pairs.into_iter().chain(Some(&data[SURROUND_PAIRS_KEY]))
+            })
+            .find_map(parse_surround_pairs)
+            .unwrap_or_default()
+    }
+
     pub fn syntax_definition_name(&self, path: &Path) -> Option<String> {
         self.data.as_ref().and_then(|data| {
             if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
@@ -318,6 +510,39 @@ impl Preferences {
         Some(command)
     }
 
+    // Type-specific patterns take precedence over global ones, and
+    // user-defined patterns over defaults. An empty pattern disables the rule.
+    fn indent_pattern(&self, path: Option<&PathBuf>, key: &'static str) -> Result<Option<Regex>> {
+        let extension = path_extension(path);
+
+        self.indent_patterns
+            .borrow_mut()
+            .entry((extension.map(String::from), key))
+            .or_insert_with(|| self.compile_indent_pattern(extension, key))
+            .clone()
+            .map_err(Error::from)
+    }
+
+    fn compile_indent_pattern(&self, extension: Option<&str>, key: &str) -> IndentPattern {
+        let pattern = self
+            .data
+            .iter()
+            .chain(Some(&self.default))
+            .flat_map(|data| {
+                let type_pattern = extension.map(|ext| &data[TYPES_KEY][ext][INDENT_KEY][key]);
+
+                type_pattern.into_iter().chain(Some(&data[INDENT_KEY][key]))
+            })
+            .find_map(|pattern| pattern.as_str());
+
+        match pattern {
+            Some(pattern) if !pattern.is_empty() => Regex::new(pattern)
+                .map(Some)
+                .map_err(|_| format!("Invalid indent pattern: {}", pattern)),
+            _ => Ok(None),
+        }
+    }
+
     fn default_open_mode_exclusions(&self) -> Result<Option<Vec<ExclusionPattern>>> {
         let exclusions = self.default[OPEN_MODE_KEY][OPEN_MODE_EXCLUSIONS_KEY]
             .as_vec()
@@ -367,6 +592,37 @@ fn load_keymap(keymap_overrides: Option<&Hash>) -> Result<KeyMap> {
     Ok(keymap)
 }
 
+fn parse_clipboard_backend(data: &Yaml) -> Option<ClipboardBackend> {
+    match data.as_str()? {
+        "system" => Some(ClipboardBackend::System),
+        "osc52" => Some(ClipboardBackend::Osc52),
+        "internal" => Some(ClipboardBackend::Internal),
+        _ => None,
+    }
+}
+
+fn parse_search_case(data: &Yaml) -> Option<SearchCase> {
+    match data.as_str()? {
+        "sensitive" => Some(SearchCase::Sensitive),
+        "insensitive" => Some(SearchCase::Insensitive),
+        "smart" => Some(SearchCase::Smart),
+        _ => None,
+    }
+}
+
+fn parse_surround_pairs(data: &Yaml) -> Option<Vec<(String, String)>> {
+    let pairs = data
+        .as_vec()?
+        .iter()
+        .filter_map(|pair| match pair.as_vec()?.as_slice() {
+            [open, close] => Some((open.as_str()?.to_string(), close.as_str()?.to_string())),
+            _ => None,
+        })
+        .collect();
+
+    Some(pairs)
+}
+
 fn path_extension(path: Option<&PathBuf>) -> Option<&str> {
     path.and_then(|p| p.extension().or_else(|| p.as_path().file_name()))
         .and_then(|e| e.to_str())
@@ -374,9 +630,15 @@ fn path_extension(path: Option<&PathBuf>) -> Option<&str> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ExclusionPattern, Preferences, YamlLoader};
-    use crate::input::KeyMap;
+    use super::{ClipboardBackend, ExclusionPattern, Preferences, YamlLoader};
+    use crate::models::application::modes::SearchCase;
+    use crate::input::{Key, KeyMap};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
     use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+    use std::time::Duration;
+    use syntect::parsing::ScopeStack;
     use yaml_rust::yaml::{Hash, Yaml};
 
     #[test]
@@ -746,12 +1008,118 @@ mod tests {
             data: None,
             keymap: KeyMap::from(&Hash::new()).unwrap(),
             theme: None,
+            search_case: None,
+            indent_patterns: RefCell::new(HashMap::new()),
         };
 
         preferences.reload().unwrap();
         assert!(preferences.keymap().get("normal").is_some());
     }
 
+    #[test]
+    fn search_case_returns_default_when_user_defined_data_not_found() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(preferences.search_case(), SearchCase::Sensitive);
+    }
+
+    #[test]
+    fn search_case_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("search:\n  case: smart").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.search_case(), SearchCase::Smart);
+    }
+
+    #[test]
+    fn search_case_returns_default_when_user_defined_data_is_invalid() {
+        let data = YamlLoader::load_from_str("search:\n  case: sometimes").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.search_case(), SearchCase::Sensitive);
+    }
+
+    #[test]
+    fn clipboard_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("clipboard: osc52").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.clipboard(), ClipboardBackend::Osc52);
+    }
+
+    #[test]
+    fn clipboard_returns_default_when_user_defined_data_is_invalid() {
+        let data = YamlLoader::load_from_str("clipboard: carrier_pigeon").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.clipboard(), ClipboardBackend::System);
+    }
+
+    #[test]
+    fn key_sequence_timeout_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("key_sequence_timeout: 250").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.key_sequence_timeout(),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn macro_keys_parses_user_defined_macros() {
+        let data = YamlLoader::load_from_str("macros:\n  quote: \"i ' escape\"").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.macro_keys("quote").unwrap(),
+            vec![Key::Char('i'), Key::Char('\''), Key::Esc]
+        );
+        assert!(preferences.macro_keys("missing").is_err());
+    }
+
+    #[test]
+    fn search_highlight_returns_default_when_no_data_provided() {
+        let preferences = Preferences::new(None);
+
+        assert!(!preferences.search_highlight());
+    }
+
+    #[test]
+    fn search_highlight_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("search:\n  highlight: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.search_highlight());
+    }
+
+    #[test]
+    fn open_mode_recency_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("open_mode:\n  recency: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!Preferences::new(None).open_mode_recency());
+        assert!(preferences.open_mode_recency());
+    }
+
+    #[test]
+    fn session_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("session: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!Preferences::new(None).session());
+        assert!(preferences.session());
+    }
+
+    #[test]
+    fn set_search_case_updates_in_memory_value() {
+        let data = YamlLoader::load_from_str("search:\n  case: smart").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        preferences.set_search_case(SearchCase::Insensitive);
+
+        assert_eq!(preferences.search_case(), SearchCase::Insensitive);
+    }
+
     #[test]
     fn format_on_save_defaults_to_false() {
         let preferences = Preferences::new(None);
@@ -828,4 +1196,120 @@ mod tests {
             Some("--check")
         );
     }
+
+    #[test]
+    fn surround_pairs_returns_user_defined_type_specific_data() {
+        let data = YamlLoader::load_from_str(
+            "surround_pairs:\n  - ['(', ')']\ntypes:\n  md:\n    surround_pairs:\n      - ['**', '**']",
+        )
+        .unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.surround_pairs(Some(&PathBuf::from("README.md"))),
+            vec![("**".to_string(), "**".to_string())]
+        );
+        assert_eq!(
+            preferences.surround_pairs(Some(&PathBuf::from("main.rs"))),
+            vec![("(".to_string(), ")".to_string())]
+        );
+    }
+
+    #[test]
+    fn surround_pairs_returns_default_when_user_defined_data_not_found() {
+        let preferences = Preferences::new(None);
+
+        assert!(preferences
+            .surround_pairs(None)
+            .contains(&("[".to_string(), "]".to_string())));
+    }
+
+    #[test]
+    fn auto_pair_returns_user_defined_type_specific_data() {
+        let data =
+            YamlLoader::load_from_str("auto_pair: true\ntypes:\n  md:\n    auto_pair: false")
+                .unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.auto_pair(Some(PathBuf::from("README.md")).as_ref()));
+        assert!(preferences.auto_pair(Some(PathBuf::from("main.rs")).as_ref()));
+    }
+
+    #[test]
+    fn auto_pair_is_disabled_by_default() {
+        let preferences = Preferences::new(None);
+
+        assert!(!preferences.auto_pair(None));
+    }
+
+    #[test]
+    fn indent_patterns_prefer_type_specific_data() {
+        let data = YamlLoader::load_from_str(
+            "types:\n  rb:\n    indent:\n      increase: 'do$'\n      decrease: ''",
+        )
+        .unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        let path = PathBuf::from("main.rb");
+
+        let increase = preferences
+            .increase_indent_pattern(Some(&path))
+            .unwrap()
+            .unwrap();
+        assert!(increase.is_match("items.each do"));
+        assert!(preferences
+            .decrease_indent_pattern(Some(&path))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn indent_patterns_fall_back_to_defaults() {
+        let preferences = Preferences::new(None);
+        let path = PathBuf::from("main.py");
+
+        let increase = preferences
+            .increase_indent_pattern(Some(&path))
+            .unwrap()
+            .unwrap();
+        assert!(increase.is_match("def main():"));
+        let decrease = preferences.decrease_indent_pattern(None).unwrap().unwrap();
+        assert!(decrease.is_match("    }"));
+    }
+
+    #[test]
+    fn fold_scope_prefers_type_specific_data() {
+        let data =
+            YamlLoader::load_from_str("fold_scope: meta.class\ntypes:\n  rb:\n    fold_scope: ''")
+                .unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences
+            .fold_scope(Some(&PathBuf::from("main.rb")))
+            .unwrap()
+            .is_none());
+        assert!(preferences
+            .fold_scope(Some(&PathBuf::from("main.rs")))
+            .unwrap()
+            .unwrap()
+            .does_match(
+                ScopeStack::from_str("source.rust meta.class")
+                    .unwrap()
+                    .as_slice()
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn fold_scope_falls_back_to_default() {
+        let preferences = Preferences::new(None);
+        let scope = preferences.fold_scope(None).unwrap().unwrap();
+
+        assert!(scope
+            .does_match(
+                ScopeStack::from_str("source.rust meta.function")
+                    .unwrap()
+                    .as_slice()
+            )
+            .is_some());
+    }
 }
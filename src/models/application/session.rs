@@ -0,0 +1,198 @@
+use crate::errors::*;
+use scribe::buffer::Position;
+use std::fs;
+use std::path::{Path, PathBuf};
+use yaml_rust::yaml::{Hash, Yaml, YamlLoader};
+use yaml_rust::YamlEmitter;
+
+const BUFFERS_KEY: &str = "buffers";
+const CURRENT_KEY: &str = "current";
+const CURSOR_LINE_KEY: &str = "line";
+const CURSOR_OFFSET_KEY: &str = "offset";
+const PATH_KEY: &str = "path";
+const SCROLL_OFFSET_KEY: &str = "scroll_offset";
+const SEARCH_QUERY_KEY: &str = "search_query";
+const SYNTAX_KEY: &str = "syntax";
+const THEME_KEY: &str = "theme";
+
+/// An open buffer, as recorded in a session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionBuffer {
+    pub path: PathBuf,
+    pub cursor: Position,
+    pub scroll_offset: usize,
+    pub syntax: Option<String>,
+}
+
+/// The editor state for a workspace, written when exiting so that
+/// it can be picked up again the next time the workspace is opened.
+#[derive(Debug, Default, PartialEq)]
+pub struct Session {
+    pub buffers: Vec<SessionBuffer>,
+    pub current: Option<usize>,
+    pub search_query: Option<String>,
+    pub theme: Option<String>,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Session> {
+        let data = fs::read_to_string(path).chain_err(|| "Couldn't read the session file")?;
+        let yaml =
+            YamlLoader::load_from_str(&data).chain_err(|| "Couldn't parse the session file")?;
+
+        Ok(yaml.first().map(Session::from_yaml).unwrap_or_default())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut data = String::new();
+        YamlEmitter::new(&mut data)
+            .dump(&self.to_yaml())
+            .chain_err(|| "Couldn't serialize the session")?;
+
+        fs::write(path, data).chain_err(|| "Couldn't write the session file")
+    }
+
+    // Entries that can't be made sense of are skipped, rather than
+    // failing to restore the rest of the session.
+    fn from_yaml(data: &Yaml) -> Session {
+        let buffers = data[BUFFERS_KEY]
+            .as_vec()
+            .map(|buffers| {
+                buffers
+                    .iter()
+                    .filter_map(|buffer| {
+                        Some(SessionBuffer {
+                            path: PathBuf::from(buffer[PATH_KEY].as_str()?),
+                            cursor: Position {
+                                line: buffer[CURSOR_LINE_KEY].as_i64().unwrap_or(0).max(0) as usize,
+                                offset: buffer[CURSOR_OFFSET_KEY].as_i64().unwrap_or(0).max(0)
+                                    as usize,
+                            },
+                            scroll_offset: buffer[SCROLL_OFFSET_KEY].as_i64().unwrap_or(0).max(0)
+                                as usize,
+                            syntax: buffer[SYNTAX_KEY].as_str().map(String::from),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Session {
+            buffers,
+            current: data[CURRENT_KEY]
+                .as_i64()
+                .map(|current| current.max(0) as usize),
+            search_query: data[SEARCH_QUERY_KEY].as_str().map(String::from),
+            theme: data[THEME_KEY].as_str().map(String::from),
+        }
+    }
+
+    fn to_yaml(&self) -> Yaml {
+        let buffers = self
+            .buffers
+            .iter()
+            .map(|buffer| {
+                let mut data = Hash::new();
+                data.insert(
+                    Yaml::from_str(PATH_KEY),
+                    Yaml::String(buffer.path.to_string_lossy().into_owned()),
+                );
+                data.insert(
+                    Yaml::from_str(CURSOR_LINE_KEY),
+                    Yaml::Integer(buffer.cursor.line as i64),
+                );
+                data.insert(
+                    Yaml::from_str(CURSOR_OFFSET_KEY),
+                    Yaml::Integer(buffer.cursor.offset as i64),
+                );
+                data.insert(
+                    Yaml::from_str(SCROLL_OFFSET_KEY),
+                    Yaml::Integer(buffer.scroll_offset as i64),
+                );
+                if let Some(ref syntax) = buffer.syntax {
+                    data.insert(Yaml::from_str(SYNTAX_KEY), Yaml::String(syntax.clone()));
+                }
+
+                Yaml::Hash(data)
+            })
+            .collect();
+
+        let mut data = Hash::new();
+        data.insert(Yaml::from_str(BUFFERS_KEY), Yaml::Array(buffers));
+        if let Some(current) = self.current {
+            data.insert(Yaml::from_str(CURRENT_KEY), Yaml::Integer(current as i64));
+        }
+        if let Some(ref query) = self.search_query {
+            data.insert(
+                Yaml::from_str(SEARCH_QUERY_KEY),
+                Yaml::String(query.clone()),
+            );
+        }
+        if let Some(ref theme) = self.theme {
+            data.insert(Yaml::from_str(THEME_KEY), Yaml::String(theme.clone()));
+        }
+
+        Yaml::Hash(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Session, SessionBuffer};
+    use scribe::buffer::Position;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    #[test]
+    fn save_and_load_round_trip_the_session() {
+        let path = env::temp_dir().join(format!("nexedit_session_{}.yml", process::id()));
+        let session = Session {
+            buffers: vec![
+                SessionBuffer {
+                    path: PathBuf::from("/src/main.rs"),
+                    cursor: Position { line: 3, offset: 4 },
+                    scroll_offset: 2,
+                    syntax: Some(String::from("Rust")),
+                },
+                SessionBuffer {
+                    path: PathBuf::from("/notes: today.md"),
+                    cursor: Position::default(),
+                    scroll_offset: 0,
+                    syntax: None,
+                },
+            ],
+            current: Some(1),
+            search_query: Some(String::from("fn main")),
+            theme: Some(String::from("solarized_light")),
+        };
+
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), session);
+    }
+
+    #[test]
+    fn load_skips_buffers_without_a_path() {
+        let path = env::temp_dir().join(format!("nexedit_partial_session_{}.yml", process::id()));
+        fs::write(&path, "buffers:\n  - line: 2\n  - path: /src/lib.rs\n").unwrap();
+
+        let session = Session::load(&path);
+        fs::remove_file(&path).unwrap();
+        let session = session.unwrap();
+
+        assert_eq!(
+            session.buffers,
+            vec![SessionBuffer {
+                path: PathBuf::from("/src/lib.rs"),
+                cursor: Position::default(),
+                scroll_offset: 0,
+                syntax: None,
+            }]
+        );
+        assert_eq!(session.current, None);
+    }
+}
@@ -1,8 +1,10 @@
 pub mod error;
 pub mod modes;
+pub mod panes;
 
 use crate::view::{Colors, StatusLineData, Style};
 use git2::{self, Repository, Status};
+use scribe::buffer::Range;
 use scribe::Workspace;
 use std::path::{Path, PathBuf};
 
@@ -38,26 +40,57 @@ fn current_buffer_status_line_data(workspace: &mut Workspace) -> StatusLineData
     }
 }
 
+fn search_status_line_data(workspace: &Workspace, highlights: &[Range]) -> StatusLineData {
+    let current = workspace
+        .current_buffer
+        .as_ref()
+        .map(|buffer| {
+            highlights
+                .iter()
+                .filter(|range| range.start() <= *buffer.cursor)
+                .count()
+        })
+        .unwrap_or(0);
+
+    // The cursor hasn't reached a match until it's at or past the first one.
+    let content = match current {
+        0 => format!(" -/{} ", highlights.len()),
+        current => format!(" {}/{} ", current, highlights.len()),
+    };
+
+    StatusLineData {
+        content,
+        style: Style::Default,
+        colors: Colors::Focused,
+    }
+}
+
 fn git_status_line_data(repo: &Option<Repository>, path: &Option<PathBuf>) -> StatusLineData {
-    let mut content = String::new();
+    StatusLineData {
+        content: git_status(repo, path),
+        style: Style::Default,
+        colors: Colors::Focused,
+    }
+}
+
+/// The path's git status, ready for display, or an
+/// empty string if it's outside of the repository.
+pub fn git_status(repo: &Option<Repository>, path: &Option<PathBuf>) -> String {
     if let Some(ref repo) = *repo {
         if let Some(ref path) = *path {
             if let Some(repo_path) = repo.workdir() {
                 if let Ok(relative_path) = path.strip_prefix(repo_path) {
                     if let Ok(status) = repo.status_file(relative_path) {
-                        content = presentable_status(&status).to_string();
+                        return presentable_status(&status).to_string();
                     }
                 }
             }
         }
     }
 
-    StatusLineData {
-        content,
-        style: Style::Default,
-        colors: Colors::Focused,
-    }
+    String::new()
 }
+
 fn presentable_status(status: &Status) -> &str {
     if status.contains(git2::Status::WT_NEW) {
         if status.contains(git2::Status::INDEX_NEW) {
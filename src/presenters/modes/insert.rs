@@ -1,24 +1,36 @@
 use crate::errors::*;
-use crate::presenters::current_buffer_status_line_data;
+use crate::presenters::{current_buffer_status_line_data, search_status_line_data};
 use crate::view::{Colors, CursorType, StatusLineData, Style, View};
+use scribe::buffer::{Position, Range};
 use scribe::Workspace;
 
-pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+pub fn display(
+    workspace: &mut Workspace,
+    view: &mut View,
+    highlights: Option<&[Range]>,
+    cursors: &[Position],
+) -> Result<()> {
     let mut presenter = view.build_presenter()?;
+    presenter.set_secondary_cursors(cursors);
     let buffer_status = current_buffer_status_line_data(workspace);
     let buf = workspace.current_buffer.as_ref().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
 
-    presenter.print_buffer(buf, &data, &workspace.syntax_set, None, None)?;
+    presenter.print_buffer(buf, &data, &workspace.syntax_set, highlights, None)?;
 
-    presenter.print_status_line(&[
+    let mut status_line = vec![
         StatusLineData {
             content: " INSERT ".to_string(),
             style: Style::Default,
             colors: Colors::Insert,
         },
         buffer_status,
-    ]);
+    ];
+    if let Some(highlights) = highlights {
+        status_line.push(search_status_line_data(workspace, highlights));
+    }
+
+    presenter.print_status_line(&status_line);
 
     presenter.set_cursor_type(CursorType::BlinkingBar);
 
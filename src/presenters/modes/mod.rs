@@ -4,7 +4,10 @@ pub mod jump;
 pub mod line_jump;
 pub mod normal;
 pub mod path;
+pub mod replace;
 pub mod search;
 pub mod search_select;
 pub mod select;
+pub mod select_block;
 pub mod select_line;
+pub mod surround;
@@ -1,21 +1,27 @@
 use crate::errors::*;
-use crate::presenters::{current_buffer_status_line_data, git_status_line_data};
+use crate::presenters::{
+    current_buffer_status_line_data, git_status_line_data, search_status_line_data,
+};
 use crate::view::{Colors, CursorType, StatusLineData, Style, View};
 use git2::Repository;
-use scribe::buffer::Position;
+use scribe::buffer::{Position, Range};
 use scribe::Workspace;
 
 pub fn display(
     workspace: &mut Workspace,
     view: &mut View,
     repo: &Option<Repository>,
+    highlights: Option<&[Range]>,
+    cursors: &[Position],
+    count: Option<usize>,
 ) -> Result<()> {
     let mut presenter = view.build_presenter()?;
+    presenter.set_secondary_cursors(cursors);
     let buffer_status = current_buffer_status_line_data(workspace);
 
     if let Some(buf) = workspace.current_buffer.as_ref() {
         let data = buf.data();
-        presenter.print_buffer(buf, &data, &workspace.syntax_set, None, None)?;
+        presenter.print_buffer(buf, &data, &workspace.syntax_set, highlights, None)?;
 
         let colors = if buf.modified() {
             Colors::Warning
@@ -23,15 +29,23 @@ pub fn display(
             Colors::Inverted
         };
 
-        presenter.print_status_line(&[
+        let mut status_line = vec![
             StatusLineData {
-                content: " NORMAL ".to_string(),
+                content: match count {
+                    Some(count) => format!(" NORMAL {} ", count),
+                    None => " NORMAL ".to_string(),
+                },
                 style: Style::Default,
                 colors,
             },
             buffer_status,
-            git_status_line_data(repo, &buf.path),
-        ]);
+        ];
+        if let Some(highlights) = highlights {
+            status_line.push(search_status_line_data(workspace, highlights));
+        }
+        status_line.push(git_status_line_data(repo, &buf.path));
+
+        presenter.print_status_line(&status_line);
 
         presenter.set_cursor_type(CursorType::Block);
 
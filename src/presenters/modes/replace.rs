@@ -0,0 +1,72 @@
+use crate::errors::*;
+use crate::models::application::modes::ReplaceMode;
+use crate::view::{Colors, CursorType, StatusLineData, Style, View};
+use scribe::buffer::Position;
+use scribe::Workspace;
+use std::fmt::Write;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn display(workspace: &mut Workspace, mode: &ReplaceMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    let buffer = workspace.current_buffer.as_ref().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    presenter.print_buffer(
+        buffer,
+        &data,
+        &workspace.syntax_set,
+        mode.search.results.as_ref().map(|r| r.as_slice()),
+        None,
+    )?;
+
+    let mut mode_display = String::with_capacity(10);
+    write!(mode_display, " {} ", mode).unwrap();
+
+    let mut replace_input = String::with_capacity(mode.input.len() + 7);
+    write!(replace_input, " with: {}", mode.input).unwrap();
+
+    let mut status_display = String::new();
+    if !mode.insert {
+        write!(
+            status_display,
+            "y: replace  n: skip  a: all  {} remaining ",
+            mode.remaining()
+        )
+        .unwrap();
+    }
+
+    let cursor_offset =
+        mode_display.graphemes(true).count() + replace_input.graphemes(true).count();
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode_display,
+            style: Style::Default,
+            colors: Colors::SearchMode,
+        },
+        StatusLineData {
+            content: replace_input,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: status_display,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    if mode.insert {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset,
+        }));
+    }
+
+    presenter.set_cursor_type(CursorType::BlinkingBar);
+
+    presenter.present()?;
+
+    Ok(())
+}
@@ -35,6 +35,7 @@ pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View) ->
             }
         }
     }
+    write!(result_display, " [{}] ", mode.case).unwrap();
 
     let cursor_offset = mode_display.graphemes(true).count() + search_input.graphemes(true).count();
 
@@ -2,11 +2,17 @@ use crate::errors::*;
 use crate::models::application::modes::SelectMode;
 use crate::presenters::current_buffer_status_line_data;
 use crate::view::{Colors, CursorType, StatusLineData, Style, View};
-use scribe::buffer::Range;
+use scribe::buffer::{Position, Range};
 use scribe::Workspace;
 
-pub fn display(workspace: &mut Workspace, mode: &SelectMode, view: &mut View) -> Result<()> {
+pub fn display(
+    workspace: &mut Workspace,
+    mode: &SelectMode,
+    view: &mut View,
+    cursors: &[Position],
+) -> Result<()> {
     let mut presenter = view.build_presenter()?;
+    presenter.set_secondary_cursors(cursors);
     let buffer_status = current_buffer_status_line_data(workspace);
     let buf = workspace.current_buffer.as_ref().ok_or(BUFFER_MISSING)?;
     let selected_range = Range::new(mode.anchor, *buf.cursor.clone());
@@ -0,0 +1,41 @@
+use crate::errors::*;
+use crate::models::application::modes::SelectBlockMode;
+use crate::presenters::current_buffer_status_line_data;
+use crate::view::{Colors, CursorType, StatusLineData, Style, View};
+use scribe::Workspace;
+
+pub fn display(
+    workspace: &mut Workspace,
+    mode: &SelectBlockMode,
+    view: &mut View,
+    tab_width: usize,
+) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buffer_status = current_buffer_status_line_data(workspace);
+    let buf = workspace.current_buffer.as_ref().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+    let selected_ranges = mode.to_ranges(&data, &buf.cursor, tab_width);
+
+    presenter.print_buffer(
+        buf,
+        &data,
+        &workspace.syntax_set,
+        Some(&selected_ranges),
+        None,
+    )?;
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: " SELECT BLOCK ".to_string(),
+            style: Style::Default,
+            colors: Colors::SelectMode,
+        },
+        buffer_status,
+    ]);
+
+    presenter.set_cursor_type(CursorType::Bar);
+
+    presenter.present()?;
+
+    Ok(())
+}
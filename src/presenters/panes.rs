@@ -0,0 +1,121 @@
+use crate::errors::*;
+use crate::presenters::{current_buffer_status_line_data, git_status_line_data};
+use crate::util;
+use crate::view::View;
+use git2::Repository;
+use scribe::buffer::Position;
+use scribe::Workspace;
+
+/// Draws every pane other than the focused one, which is left to the current
+/// mode's presenter, and is presented along with it. Only the current buffer
+/// can be reached, so the workspace is cycled through once, drawing panes as
+/// their buffer comes up. Panes showing the same buffer as another keep their
+/// own cursor, which is swapped in while they're drawn.
+pub fn display(
+    workspace: &mut Workspace,
+    view: &mut View,
+    repo: &Option<Repository>,
+) -> Result<()> {
+    view.update_focused_pane(workspace.current_buffer.as_ref());
+    let mut panes = view.unfocused_panes();
+    let start = current_buffer_id(workspace);
+
+    let mut result = Ok(());
+    loop {
+        let current = current_buffer_id(workspace);
+        let (shown, hidden): (Vec<_>, Vec<_>) = panes
+            .into_iter()
+            .partition(|(_, pane)| current.is_some() && pane.buffer_id == current);
+        panes = hidden;
+
+        for (id, pane) in shown {
+            if result.is_ok() {
+                result = draw(workspace, view, repo, id, Some(pane.cursor));
+            }
+        }
+
+        if panes.iter().all(|(_, pane)| pane.buffer_id.is_none()) {
+            break;
+        }
+        workspace.next_buffer();
+        if current_buffer_id(workspace) == start {
+            break;
+        }
+    }
+    if let Some(start) = start {
+        util::select_buffer(workspace, start);
+    }
+    result?;
+
+    // Panes whose buffer has since been closed are left blank.
+    for (id, _) in panes {
+        draw(workspace, view, repo, id, None)?;
+    }
+
+    Ok(())
+}
+
+fn current_buffer_id(workspace: &Workspace) -> Option<usize> {
+    workspace
+        .current_buffer
+        .as_ref()
+        .and_then(|buffer| buffer.id)
+}
+
+fn draw(
+    workspace: &mut Workspace,
+    view: &mut View,
+    repo: &Option<Repository>,
+    pane: usize,
+    cursor: Option<Position>,
+) -> Result<()> {
+    let focused_cursor = match (cursor, workspace.current_buffer.as_mut()) {
+        (Some(cursor), Some(buffer)) => {
+            let focused_cursor = buffer.cursor.clone();
+            buffer.cursor.move_to(cursor);
+
+            Some(focused_cursor)
+        }
+        _ => None,
+    };
+
+    let result = print_pane(workspace, view, repo, pane, focused_cursor.is_some());
+
+    if let (Some(buffer), Some(cursor)) = (workspace.current_buffer.as_mut(), focused_cursor) {
+        buffer.cursor = cursor;
+    }
+
+    result
+}
+
+fn print_pane(
+    workspace: &mut Workspace,
+    view: &mut View,
+    repo: &Option<Repository>,
+    pane: usize,
+    shown: bool,
+) -> Result<()> {
+    let status_line = [
+        current_buffer_status_line_data(workspace),
+        git_status_line_data(
+            repo,
+            &workspace
+                .current_buffer
+                .as_ref()
+                .and_then(|buf| buf.path.clone()),
+        ),
+    ];
+    let data = workspace
+        .current_buffer
+        .as_ref()
+        .filter(|_| shown)
+        .map(|buf| buf.data());
+
+    let mut presenter = view.build_pane_presenter(pane)?;
+    if let (Some(buffer), Some(data)) = (workspace.current_buffer.as_ref(), data.as_ref()) {
+        presenter.print_buffer(buffer, data, &workspace.syntax_set, None, None)?;
+        presenter.print_status_line(&status_line);
+    }
+    presenter.set_cursor(None);
+    presenter.draw()
+}
@@ -0,0 +1,170 @@
+use scribe::buffer::{Position, Token};
+use std::str::FromStr;
+use syntect::highlighting::ScopeSelectors;
+use syntect::parsing::{ScopeStack, ScopeStackOp};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Brackets in these scopes are prose rather than code, and aren't matched.
+const IGNORED_SCOPES: &str = "string, comment";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bracket {
+    pub position: Position,
+    pub character: char,
+}
+
+/// Finds the index of the bracket matching the one at `index`,
+/// skipping over nested pairs of the same type.
+pub fn find_match(brackets: &[Bracket], index: usize) -> Option<usize> {
+    let bracket = brackets.get(index)?;
+    let (partner, forward) = partner(bracket.character)?;
+    let mut depth = 0;
+
+    let candidates: Box<dyn Iterator<Item = usize>> = if forward {
+        Box::new(index + 1..brackets.len())
+    } else {
+        Box::new((0..index).rev())
+    };
+    for candidate in candidates {
+        let character = brackets[candidate].character;
+        if character == bracket.character {
+            depth += 1;
+        } else if character == partner {
+            if depth == 0 {
+                return Some(candidate);
+            }
+            depth -= 1;
+        }
+    }
+
+    None
+}
+
+/// The brackets in a buffer's tokens, leaving out those in strings and comments.
+pub fn from_tokens<'a, T>(tokens: T) -> Vec<Bracket>
+where
+    T: Iterator<Item = Token<'a>>,
+{
+    let selectors = ignored_scopes();
+    let mut brackets = Vec::new();
+
+    for token in tokens {
+        if let Token::Lexeme(lexeme) = token {
+            if selectors.does_match(lexeme.scope.as_slice()).is_some() {
+                continue;
+            }
+
+            for (offset, grapheme) in lexeme.value.graphemes(true).enumerate() {
+                if let Some(character) = bracket_char(grapheme) {
+                    brackets.push(Bracket {
+                        position: Position {
+                            line: lexeme.position.line,
+                            offset: lexeme.position.offset + offset,
+                        },
+                        character,
+                    });
+                }
+            }
+        }
+    }
+
+    brackets
+}
+
+/// The byte offsets of brackets in a parsed line, given the scope stack at its
+/// start and the parser's scope changes, leaving out those in strings and comments.
+pub fn line_offsets(
+    line: &str,
+    mut scopes: ScopeStack,
+    ops: &[(usize, ScopeStackOp)],
+) -> Vec<usize> {
+    let selectors = ignored_scopes();
+    let mut ops = ops.iter().peekable();
+    let mut offsets = Vec::new();
+
+    for (offset, character) in line.char_indices() {
+        while let Some((_, op)) = ops.next_if(|(op_offset, _)| *op_offset <= offset) {
+            let _ = scopes.apply(op);
+        }
+
+        if partner(character).is_some() && selectors.does_match(scopes.as_slice()).is_none() {
+            offsets.push(offset);
+        }
+    }
+
+    offsets
+}
+
+pub fn bracket_char(grapheme: &str) -> Option<char> {
+    let mut chars = grapheme.chars();
+    let character = chars.next()?;
+
+    match (partner(character), chars.next()) {
+        (Some(_), None) => Some(character),
+        _ => None,
+    }
+}
+
+// The bracket that pairs with the specified one,
+// and whether it's found ahead of it.
+fn partner(bracket: char) -> Option<(char, bool)> {
+    match bracket {
+        '(' => Some((')', true)),
+        '[' => Some((']', true)),
+        '{' => Some(('}', true)),
+        ')' => Some(('(', false)),
+        ']' => Some(('[', false)),
+        '}' => Some(('{', false)),
+        _ => None,
+    }
+}
+
+fn ignored_scopes() -> ScopeSelectors {
+    ScopeSelectors::from_str(IGNORED_SCOPES).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_match, from_tokens, Bracket};
+    use scribe::buffer::Position;
+    use scribe::{Buffer, Workspace};
+    use std::path::Path;
+
+    fn bracket(offset: usize, character: char) -> Bracket {
+        Bracket {
+            position: Position { line: 0, offset },
+            character,
+        }
+    }
+
+    #[test]
+    fn find_match_skips_nested_pairs() {
+        let brackets = vec![
+            bracket(0, '('),
+            bracket(1, '['),
+            bracket(2, '('),
+            bracket(3, ')'),
+            bracket(4, ']'),
+            bracket(5, ')'),
+        ];
+
+        assert_eq!(find_match(&brackets, 0), Some(5));
+        assert_eq!(find_match(&brackets, 4), Some(1));
+        assert_eq!(find_match(&brackets[..5], 0), None);
+    }
+
+    #[test]
+    fn from_tokens_ignores_brackets_in_strings_and_comments() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("f(\")\") // (\n");
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        workspace.add_buffer(buffer);
+        let tokens = workspace.current_buffer_tokens().unwrap();
+
+        assert_eq!(
+            from_tokens(tokens.iter().unwrap()),
+            vec![bracket(1, '('), bracket(5, ')')]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::util::brackets::{self, Bracket};
+use scribe::buffer::{LineRange, Position, Token};
+use std::mem;
+use syntect::highlighting::ScopeSelectors;
+
+/// Whether a line is hidden by one of the specified folds.
+pub fn hidden(folds: &[LineRange], line: usize) -> bool {
+    folds
+        .iter()
+        .any(|fold| fold.start() < line && line < fold.end())
+}
+
+/// The block of lines indented beneath the specified line or, failing that,
+/// the one containing it. Blocks start with the line introducing them.
+pub fn indentation_block(data: &str, line: usize) -> Option<LineRange> {
+    let lines: Vec<&str> = data.lines().collect();
+
+    let level = match indentation(lines.get(line)?) {
+        Some(level) => {
+            if let Some(end) = block_end(&lines, line, level) {
+                return Some(LineRange::new(line, end));
+            }
+
+            level
+        }
+        // Blank lines belong to the block of the line that follows them.
+        None => lines[line..].iter().find_map(|line| indentation(line))?,
+    };
+    let header = (0..line)
+        .rev()
+        .find(|&index| indentation(lines[index]).is_some_and(|indent| indent < level))?;
+    let end = block_end(&lines, header, indentation(lines[header])?)?;
+
+    Some(LineRange::new(header, end))
+}
+
+/// The lines spanned by the run of lexemes whose scopes match the
+/// selectors, containing the specified line. Runs on a single line
+/// don't make for much of a fold, and aren't considered.
+pub fn scope_block<'a, T>(
+    tokens: T,
+    brackets: &[Bracket],
+    selectors: &ScopeSelectors,
+    line: usize,
+) -> Option<LineRange>
+where
+    T: Iterator<Item = Token<'a>>,
+{
+    let mut run = Vec::new();
+    let mut runs = Vec::new();
+
+    for token in tokens {
+        if let Token::Lexeme(lexeme) = token {
+            if selectors.does_match(lexeme.scope.as_slice()).is_some() {
+                run.push(lexeme.position);
+            } else if !run.is_empty() {
+                runs.push(mem::take(&mut run));
+            }
+        }
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+
+    runs.iter()
+        .flat_map(|run| split_run(run, brackets))
+        .find(|&(start, end)| start <= line && line <= end && start < end)
+        .map(|(start, end)| LineRange::new(start, end + 1))
+}
+
+// Neighbouring regions (e.g. functions separated by a blank line) can
+// make up a single run of lexemes, so runs are split after the end of
+// each brace-delimited block, giving the lines spanned by each part.
+fn split_run(run: &[Position], brackets: &[Bracket]) -> Vec<(usize, usize)> {
+    let mut parts = Vec::new();
+    let mut remaining = run;
+
+    while let (Some(first), Some(last)) = (remaining.first(), remaining.last()) {
+        let close = brackets
+            .iter()
+            .position(|bracket| {
+                bracket.character == '{' && bracket.position >= *first && bracket.position <= *last
+            })
+            .and_then(|open| brackets::find_match(brackets, open))
+            .map(|close| brackets[close].position)
+            .filter(|close| close <= last);
+
+        match close {
+            Some(close) => {
+                parts.push((first.line, close.line));
+                let next = remaining
+                    .iter()
+                    .position(|position| *position > close)
+                    .unwrap_or(remaining.len());
+                remaining = &remaining[next..];
+            }
+            None => {
+                parts.push((first.line, last.line));
+                break;
+            }
+        }
+    }
+
+    parts
+}
+
+// The end of the lines following the header that are indented beyond
+// the specified level, if there are any. Trailing blank lines are left out.
+fn block_end(lines: &[&str], header: usize, level: usize) -> Option<usize> {
+    let mut end = None;
+
+    for (index, line) in lines.iter().enumerate().skip(header + 1) {
+        match indentation(line) {
+            Some(indent) if indent > level => end = Some(index + 1),
+            Some(_) => break,
+            None => (),
+        }
+    }
+
+    end
+}
+
+// The width of a line's leading whitespace, or nothing for blank lines.
+fn indentation(line: &str) -> Option<usize> {
+    let content = line.trim_start();
+
+    if content.is_empty() {
+        None
+    } else {
+        Some(line.len() - content.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{indentation_block, scope_block};
+    use crate::util::brackets;
+    use scribe::buffer::LineRange;
+    use scribe::{Buffer, Workspace};
+    use std::path::Path;
+    use std::str::FromStr;
+    use syntect::highlighting::ScopeSelectors;
+
+    #[test]
+    fn indentation_block_prefers_the_block_beneath_the_line() {
+        let data = "a\n  b\n    c\n\n    d\n  e\nf\n";
+
+        assert_eq!(indentation_block(data, 1), Some(LineRange::new(1, 5)));
+        assert_eq!(indentation_block(data, 4), Some(LineRange::new(1, 5)));
+        assert_eq!(indentation_block(data, 3), Some(LineRange::new(1, 5)));
+        assert_eq!(indentation_block(data, 5), Some(LineRange::new(0, 6)));
+        assert_eq!(indentation_block(data, 6), None);
+    }
+
+    #[test]
+    fn scope_block_spans_the_matching_region_around_the_line() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("use std;\n\nfn main() {\n    run();\n}\n");
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        workspace.add_buffer(buffer);
+        let tokens = workspace.current_buffer_tokens().unwrap();
+        let brackets = brackets::from_tokens(tokens.iter().unwrap());
+        let selectors = ScopeSelectors::from_str("meta.function").unwrap();
+
+        assert_eq!(
+            scope_block(tokens.iter().unwrap(), &brackets, &selectors, 3),
+            Some(LineRange::new(2, 5))
+        );
+        assert_eq!(
+            scope_block(tokens.iter().unwrap(), &brackets, &selectors, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn scope_block_separates_neighbouring_regions() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn a() {\n    x();\n}\n\nfn b() {\n    y();\n}\n");
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        workspace.add_buffer(buffer);
+        let tokens = workspace.current_buffer_tokens().unwrap();
+        let brackets = brackets::from_tokens(tokens.iter().unwrap());
+        let selectors = ScopeSelectors::from_str("meta.function").unwrap();
+
+        assert_eq!(
+            scope_block(tokens.iter().unwrap(), &brackets, &selectors, 1),
+            Some(LineRange::new(0, 3))
+        );
+        assert_eq!(
+            scope_block(tokens.iter().unwrap(), &brackets, &selectors, 5),
+            Some(LineRange::new(4, 7))
+        );
+    }
+}
@@ -1,13 +1,21 @@
 pub use self::selectable_vec::SelectableVec;
 
+pub mod brackets;
+pub mod fold;
 pub mod movement_lexer;
 pub mod reflow;
 mod selectable_vec;
+pub mod surround;
+pub mod text_object;
 pub mod token;
 
 use crate::errors::*;
+use crate::models::application::Mode;
 use crate::models::Application;
 use scribe::buffer::{Buffer, LineRange, Position, Range};
+use scribe::Workspace;
+use std::cmp::Reverse;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn inclusive_range(line_range: &LineRange, buffer: &mut Buffer) -> Range {
     let data = buffer.data();
@@ -42,6 +50,25 @@ pub fn inclusive_range(line_range: &LineRange, buffer: &mut Buffer) -> Range {
     )
 }
 
+/// Cycles through the workspace's buffers until the one with the
+/// specified id is current, returning false if it isn't open.
+pub fn select_buffer(workspace: &mut Workspace, id: usize) -> bool {
+    let Some(start) = workspace.current_buffer.as_ref().and_then(|buf| buf.id) else {
+        return false;
+    };
+
+    let mut current = start;
+    while current != id {
+        workspace.next_buffer();
+        current = match workspace.current_buffer.as_ref().and_then(|buf| buf.id) {
+            Some(current) if current != start => current,
+            _ => return false,
+        };
+    }
+
+    true
+}
+
 pub fn add_buffer(buffer: Buffer, app: &mut Application) -> Result<()> {
     app.workspace.add_buffer(buffer);
     app.view
@@ -50,8 +77,127 @@ pub fn add_buffer(buffer: Buffer, app: &mut Application) -> Result<()> {
     Ok(())
 }
 
+// Runs a command once for every cursor in the current buffer, as a
+// single undo step. Cursors are visited in document order and tracked
+// by their distance from the end of the buffer, which an edit at an
+// earlier cursor doesn't change. In select mode, each cursor's selection
+// anchor is tracked the same way, and restored before the command runs.
+pub fn at_each_cursor<F>(app: &mut Application, mut command: F) -> Result<()>
+where
+    F: FnMut(&mut Application) -> Result<()>,
+{
+    let buffer = app
+        .workspace
+        .current_buffer
+        .as_mut()
+        .ok_or(BUFFER_MISSING)?;
+    let secondary_cursors = app.cursors.positions(buffer);
+    if secondary_cursors.is_empty()
+        || !matches!(app.mode, Mode::Normal | Mode::Insert | Mode::Select(_))
+    {
+        return command(app);
+    }
+
+    let data = buffer.data();
+    let length = data.graphemes(true).count();
+    let distance = |position: &Position| length - grapheme_index(&data, position);
+    let primary_anchor = match app.mode {
+        Mode::Select(ref mode) => Some(mode.anchor),
+        _ => None,
+    };
+    let mut cursors: Vec<(usize, Option<usize>, bool)> = secondary_cursors
+        .iter()
+        .zip(app.cursors.anchors(buffer))
+        .map(|(position, anchor)| (distance(position), anchor.as_ref().map(distance), false))
+        .collect();
+    cursors.push((
+        distance(&buffer.cursor),
+        primary_anchor.as_ref().map(distance),
+        true,
+    ));
+    cursors.sort_by_key(|&(distance_from_end, _, _)| Reverse(distance_from_end));
+
+    app.hold_operation_group();
+    let mut result = Ok(());
+    let mut selections = Vec::new();
+    for (distance_from_end, anchor_distance, primary) in cursors {
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        let data = buffer.data();
+        let length = data.graphemes(true).count();
+        let position = grapheme_position(&data, length.saturating_sub(distance_from_end));
+        buffer.cursor.move_to(position);
+        if let Mode::Select(ref mut mode) = app.mode {
+            mode.anchor = anchor_distance
+                .map(|distance| grapheme_position(&data, length.saturating_sub(distance)))
+                .unwrap_or(position);
+        }
+
+        if result.is_ok() {
+            result = command(app);
+        }
+        let anchor = match app.mode {
+            Mode::Select(ref mode) => Some(mode.anchor),
+            _ => None,
+        };
+        let cursor = *app.workspace.current_buffer.as_ref().unwrap().cursor;
+        selections.push(((cursor, anchor), primary));
+    }
+    app.release_operation_group();
+
+    let buffer = app.workspace.current_buffer.as_mut().unwrap();
+    if let Some(((position, anchor), _)) = selections.iter().find(|(_, primary)| *primary) {
+        buffer.cursor.move_to(*position);
+        if let (Mode::Select(ref mut mode), Some(anchor)) = (&mut app.mode, anchor) {
+            mode.anchor = *anchor;
+        }
+    }
+    app.cursors.set_selections(
+        buffer,
+        selections
+            .into_iter()
+            .filter(|(_, primary)| !primary)
+            .map(|(selection, _)| selection)
+            .collect(),
+    );
+
+    result
+}
+
+fn grapheme_index(data: &str, position: &Position) -> usize {
+    let mut index = 0;
+    for (line_number, line) in data.split_inclusive('\n').enumerate() {
+        if line_number == position.line {
+            let line_length = line.trim_end_matches('\n').graphemes(true).count();
+
+            return index + position.offset.min(line_length);
+        }
+        index += line.graphemes(true).count();
+    }
+
+    index
+}
+
+fn grapheme_position(data: &str, mut index: usize) -> Position {
+    let mut line_number = 0;
+    for line in data.split_inclusive('\n') {
+        let line_length = line.graphemes(true).count();
+        if index < line_length || !line.ends_with('\n') {
+            break;
+        }
+
+        index -= line_length;
+        line_number += 1;
+    }
+
+    Position {
+        line: line_number,
+        offset: index,
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::models::Application;
     use scribe::buffer::{LineRange, Position, Range};
     use scribe::Buffer;
 
@@ -84,4 +230,83 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn grapheme_index_and_position_convert_between_each_other() {
+        let data = "nexedit\nƒoo\n";
+        let position = Position { line: 1, offset: 2 };
+
+        assert_eq!(super::grapheme_index(data, &position), 10);
+        assert_eq!(super::grapheme_position(data, 10), position);
+        assert_eq!(
+            super::grapheme_position(data, 12),
+            Position { line: 2, offset: 0 }
+        );
+    }
+
+    #[test]
+    fn at_each_cursor_runs_the_command_at_every_cursor_as_one_undo_step() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\nnexedit\nnexedit");
+        app.workspace.add_buffer(buffer);
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        app.cursors.set(
+            buffer,
+            vec![
+                Position { line: 1, offset: 0 },
+                Position { line: 2, offset: 0 },
+            ],
+        );
+
+        super::at_each_cursor(&mut app, |app| {
+            let buffer = app.workspace.current_buffer.as_mut().unwrap();
+            buffer.insert("a\n");
+            buffer.cursor.move_down();
+
+            Ok(())
+        })
+        .unwrap();
+
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        assert_eq!(buffer.data(), "a\nnexedit\na\nnexedit\na\nnexedit");
+        assert_eq!(*buffer.cursor, Position { line: 1, offset: 0 });
+        assert_eq!(
+            app.cursors.positions(buffer),
+            &[
+                Position { line: 3, offset: 0 },
+                Position { line: 5, offset: 0 },
+            ]
+        );
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "nexedit\nnexedit\nnexedit");
+    }
+
+    #[test]
+    fn at_each_cursor_leaves_operation_groups_it_did_not_start_open() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("nexedit\nnexedit");
+        app.workspace.add_buffer(buffer);
+        let buffer = app.workspace.current_buffer.as_ref().unwrap();
+        app.cursors
+            .set(buffer, vec![Position { line: 1, offset: 0 }]);
+
+        app.hold_operation_group();
+        for _ in 0..2 {
+            super::at_each_cursor(&mut app, |app| {
+                app.workspace.current_buffer.as_mut().unwrap().insert("a");
+
+                Ok(())
+            })
+            .unwrap();
+        }
+        app.release_operation_group();
+
+        let buffer = app.workspace.current_buffer.as_mut().unwrap();
+        assert_eq!(buffer.data(), "aanexedit\naanexedit");
+        buffer.undo();
+        assert_eq!(buffer.data(), "nexedit\nnexedit");
+    }
 }
@@ -1,4 +1,5 @@
 use std::ops::Deref;
+use std::slice::IterMut;
 
 pub struct SelectableVec<T> {
     set: Vec<T>,
@@ -36,6 +37,29 @@ impl<T> SelectableVec<T> {
             self.selected_index = 0;
         }
     }
+
+    /// Adds elements to the end of the set, leaving the selection as-is.
+    pub fn extend<I: IntoIterator<Item = T>>(&mut self, elements: I) {
+        self.set.extend(elements);
+    }
+
+    /// Elements can be changed in place, though not added or removed.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.set.iter_mut()
+    }
+
+    pub fn remove_selection(&mut self) -> Option<T> {
+        if self.selected_index >= self.set.len() {
+            return None;
+        }
+
+        let selection = self.set.remove(self.selected_index);
+        if self.selected_index >= self.set.len() {
+            self.selected_index = 0;
+        }
+
+        Some(selection)
+    }
 }
 
 impl<T> Deref for SelectableVec<T> {
@@ -77,4 +101,38 @@ mod tests {
         selectable_vec.select_previous();
         assert_eq!(selectable_vec.selection(), Some(&1));
     }
+
+    #[test]
+    fn remove_selection_selects_the_following_element() {
+        let mut selectable_vec: SelectableVec<usize> = SelectableVec::new(vec![0, 1, 2]);
+        selectable_vec.select_next();
+
+        assert_eq!(selectable_vec.remove_selection(), Some(1));
+        assert_eq!(selectable_vec.selection(), Some(&2));
+    }
+
+    #[test]
+    fn remove_selection_wraps_at_end_of_set() {
+        let mut selectable_vec: SelectableVec<usize> = SelectableVec::new(vec![0, 1]);
+        selectable_vec.select_previous();
+
+        assert_eq!(selectable_vec.remove_selection(), Some(1));
+        assert_eq!(selectable_vec.selection(), Some(&0));
+    }
+
+    #[test]
+    fn extend_keeps_the_selection() {
+        let mut selectable_vec: SelectableVec<usize> = SelectableVec::new(vec![0, 1]);
+        selectable_vec.select_next();
+        selectable_vec.extend(vec![2, 3]);
+
+        assert_eq!(*selectable_vec, vec![0, 1, 2, 3]);
+        assert_eq!(selectable_vec.selection(), Some(&1));
+    }
+
+    #[test]
+    fn remove_selection_returns_none_when_the_set_is_empty() {
+        let mut selectable_vec: SelectableVec<usize> = SelectableVec::new(Vec::new());
+        assert!(selectable_vec.remove_selection().is_none());
+    }
 }
@@ -0,0 +1,285 @@
+use regex::Regex;
+use scribe::buffer::{Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+lazy_static! {
+    static ref TAG: Regex = Regex::new(r"<(/)?([A-Za-z][^\s/>]*)[^>]*?(/)?>").unwrap();
+}
+
+/// The delimiters enclosing a region of text.
+#[derive(Debug, PartialEq)]
+pub struct Surrounding {
+    pub open: Range,
+    pub close: Range,
+}
+
+/// The configured pair whose opening or closing
+/// delimiter is the specified character.
+pub fn pair_for(c: char, pairs: &[(String, String)]) -> Option<(String, String)> {
+    let c = c.to_string();
+
+    pairs
+        .iter()
+        .find(|(open, close)| *open == c || *close == c)
+        .cloned()
+}
+
+/// The opening and closing tags for an element, using
+/// the input's first word as the name of the closing tag.
+pub fn tag_pair(input: &str) -> Option<(String, String)> {
+    let input = input.trim();
+    let name = input.split_whitespace().next()?;
+
+    Some((format!("<{}>", input), format!("</{}>", name)))
+}
+
+/// Finds the innermost pair of delimiters (or HTML/XML tags) enclosing
+/// the cursor. Pairs using the same delimiter on both sides, like quotes,
+/// are only matched on the cursor's line.
+pub fn find(data: &str, cursor: &Position, pairs: &[(String, String)]) -> Option<Surrounding> {
+    let cursor_index = byte_index(data, cursor)?;
+    let mut candidates = Vec::new();
+
+    for (open, close) in pairs {
+        if open.is_empty() || close.is_empty() {
+            continue;
+        }
+
+        if open == close {
+            candidates.extend(quote_pairs(data, cursor_index, open));
+        } else {
+            let mut delimiters: Vec<(usize, usize, bool)> = data
+                .match_indices(open.as_str())
+                .map(|(index, _)| (index, index + open.len(), true))
+                .chain(
+                    data.match_indices(close.as_str())
+                        .map(|(index, _)| (index, index + close.len(), false)),
+                )
+                .collect();
+            delimiters.sort();
+            candidates.extend(nested_pairs(delimiters.into_iter()));
+        }
+    }
+    candidates.extend(tag_pairs(data));
+
+    candidates
+        .into_iter()
+        .filter(|(open, close)| open.0 <= cursor_index && cursor_index < close.1)
+        .max_by_key(|(open, _)| open.0)
+        .map(|(open, close)| Surrounding {
+            open: Range::new(position_at(data, open.0), position_at(data, open.1)),
+            close: Range::new(position_at(data, close.0), position_at(data, close.1)),
+        })
+}
+
+// Pairs up opening and closing delimiters, given as byte ranges
+// in document order, matching nested ones from the inside out.
+fn nested_pairs<I>(delimiters: I) -> Vec<((usize, usize), (usize, usize))>
+where
+    I: Iterator<Item = (usize, usize, bool)>,
+{
+    let mut pairs = Vec::new();
+    let mut opened = Vec::new();
+
+    for (start, end, is_open) in delimiters {
+        if is_open {
+            opened.push((start, end));
+        } else if let Some(open) = opened.pop() {
+            pairs.push((open, (start, end)));
+        }
+    }
+
+    pairs
+}
+
+// Pairs up tags with the same name. Opening tags that are never closed
+// (e.g. void elements like <br>) are dropped once an enclosing tag closes,
+// and closing tags without a matching opening tag are ignored.
+fn tag_pairs(data: &str) -> Vec<((usize, usize), (usize, usize))> {
+    let mut pairs = Vec::new();
+    let mut opened: Vec<(&str, (usize, usize))> = Vec::new();
+
+    for tag in TAG.captures_iter(data) {
+        let (Some(matched), Some(name)) = (tag.get(0), tag.get(2)) else {
+            continue;
+        };
+
+        // Self-closing tags don't enclose anything.
+        if tag.get(3).is_some() {
+            continue;
+        }
+
+        let range = (matched.start(), matched.end());
+        if tag.get(1).is_none() {
+            opened.push((name.as_str(), range));
+        } else if let Some(index) = opened
+            .iter()
+            .rposition(|(open_name, _)| open_name.eq_ignore_ascii_case(name.as_str()))
+        {
+            pairs.push((opened[index].1, range));
+            opened.truncate(index);
+        }
+    }
+
+    pairs
+}
+
+fn quote_pairs(
+    data: &str,
+    cursor_index: usize,
+    quote: &str,
+) -> Vec<((usize, usize), (usize, usize))> {
+    let line_start = data[..cursor_index].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = data[cursor_index..]
+        .find('\n')
+        .map_or(data.len(), |i| cursor_index + i);
+    let line = &data[line_start..line_end];
+
+    let quotes: Vec<(usize, usize)> = line
+        .match_indices(quote)
+        .filter(|(index, _)| !line[..*index].ends_with('\\'))
+        .map(|(index, _)| (line_start + index, line_start + index + quote.len()))
+        .collect();
+
+    quotes
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+fn byte_index(data: &str, position: &Position) -> Option<usize> {
+    let mut index = 0;
+    for (line_number, line) in data.split_inclusive('\n').enumerate() {
+        if line_number == position.line {
+            let content = line.trim_end_matches('\n');
+
+            return content
+                .grapheme_indices(true)
+                .map(|(offset, _)| offset)
+                .chain(Some(content.len()))
+                .nth(position.offset)
+                .map(|offset| index + offset);
+        }
+        index += line.len();
+    }
+
+    None
+}
+
+fn position_at(data: &str, index: usize) -> Position {
+    let preceding = &data[..index];
+    let line_start = preceding.rfind('\n').map_or(0, |i| i + 1);
+
+    Position {
+        line: preceding.matches('\n').count(),
+        offset: preceding[line_start..].graphemes(true).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, tag_pair, Surrounding};
+    use scribe::buffer::{Position, Range};
+
+    fn pairs() -> Vec<(String, String)> {
+        vec![
+            ("(".to_string(), ")".to_string()),
+            ("\"".to_string(), "\"".to_string()),
+        ]
+    }
+
+    fn range(line: usize, start: usize, end: usize) -> Range {
+        Range::new(
+            Position {
+                line,
+                offset: start,
+            },
+            Position { line, offset: end },
+        )
+    }
+
+    #[test]
+    fn find_returns_the_innermost_pair_around_the_cursor() {
+        let data = "f(\"a\", (b))";
+
+        assert_eq!(
+            find(data, &Position { line: 0, offset: 8 }, &pairs()),
+            Some(Surrounding {
+                open: range(0, 7, 8),
+                close: range(0, 9, 10),
+            })
+        );
+        assert_eq!(
+            find(data, &Position { line: 0, offset: 3 }, &pairs()),
+            Some(Surrounding {
+                open: range(0, 2, 3),
+                close: range(0, 4, 5),
+            })
+        );
+        assert_eq!(find(data, &Position { line: 0, offset: 0 }, &pairs()), None);
+    }
+
+    #[test]
+    fn find_matches_enclosing_tags() {
+        let data = "<p class=\"x\">one <br/> <b>two</b></p>";
+
+        assert_eq!(
+            find(
+                data,
+                &Position {
+                    line: 0,
+                    offset: 14
+                },
+                &[]
+            ),
+            Some(Surrounding {
+                open: range(0, 0, 13),
+                close: range(0, 33, 37),
+            })
+        );
+    }
+
+    #[test]
+    fn find_skips_unmatched_and_void_tags() {
+        let data = "<div><p>a<br>b</p></span></div>";
+
+        assert_eq!(
+            find(
+                data,
+                &Position {
+                    line: 0,
+                    offset: 13
+                },
+                &[]
+            ),
+            Some(Surrounding {
+                open: range(0, 5, 8),
+                close: range(0, 14, 18),
+            })
+        );
+        assert_eq!(
+            find(
+                data,
+                &Position {
+                    line: 0,
+                    offset: 18
+                },
+                &[]
+            ),
+            Some(Surrounding {
+                open: range(0, 0, 5),
+                close: range(0, 25, 31),
+            })
+        );
+    }
+
+    #[test]
+    fn tag_pair_closes_with_the_tag_name() {
+        assert_eq!(
+            tag_pair("a href=\"/\""),
+            Some(("<a href=\"/\">".to_string(), "</a>".to_string()))
+        );
+        assert_eq!(tag_pair(" "), None);
+    }
+}
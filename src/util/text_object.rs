@@ -0,0 +1,639 @@
+use scribe::buffer::{Position, Range, Token};
+use std::str::FromStr;
+use syntect::highlighting::ScopeSelectors;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A region of text surrounding the cursor, selected either
+/// by its contents alone (inner) or including its delimiters,
+/// surrounding whitespace or blank lines (around).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextObject {
+    Word,
+    Quoted(char),
+    Delimited(char, char),
+    Paragraph,
+    Syntax(SyntaxObject),
+}
+
+/// Text objects identified using the buffer's syntax definition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SyntaxObject {
+    Function,
+    String,
+    Comment,
+}
+
+impl TextObject {
+    /// The text object named by the key typed after "i" or "a".
+    pub fn from_char(c: char) -> Option<TextObject> {
+        match c {
+            'w' => Some(TextObject::Word),
+            '"' | '\'' | '`' => Some(TextObject::Quoted(c)),
+            '(' | ')' | 'b' => Some(TextObject::Delimited('(', ')')),
+            '[' | ']' => Some(TextObject::Delimited('[', ']')),
+            '{' | '}' | 'B' => Some(TextObject::Delimited('{', '}')),
+            '<' | '>' => Some(TextObject::Delimited('<', '>')),
+            'p' => Some(TextObject::Paragraph),
+            'f' => Some(TextObject::Syntax(SyntaxObject::Function)),
+            's' => Some(TextObject::Syntax(SyntaxObject::String)),
+            'c' => Some(TextObject::Syntax(SyntaxObject::Comment)),
+            _ => None,
+        }
+    }
+}
+
+impl SyntaxObject {
+    fn selectors(&self) -> &'static str {
+        match *self {
+            SyntaxObject::Function => "meta.function, meta.method",
+            SyntaxObject::String => "string",
+            SyntaxObject::Comment => "comment",
+        }
+    }
+}
+
+/// Finds the range of a text object around the cursor. Syntax objects
+/// aren't found this way; use `find_syntax_object` for those instead.
+pub fn find(data: &str, cursor: &Position, object: TextObject, around: bool) -> Option<Range> {
+    let graphemes = Graphemes::new(data);
+    let index = graphemes.index_of(cursor)?;
+
+    match object {
+        TextObject::Word => word(&graphemes, index, around),
+        TextObject::Quoted(quote) => quoted(&graphemes, index, quote, around),
+        TextObject::Delimited(open, close) => delimited(&graphemes, index, open, close, around),
+        TextObject::Paragraph => paragraph(data, cursor, around),
+        TextObject::Syntax(_) => None,
+    }
+}
+
+/// Finds the run of lexemes around the cursor whose scopes match the
+/// object. The inner range leaves out the object's punctuation, such as
+/// quotes around a string, or everything outside of a function's body.
+pub fn find_syntax_object<'a, T>(
+    tokens: T,
+    data: &str,
+    cursor: &Position,
+    object: SyntaxObject,
+    around: bool,
+) -> Option<Range>
+where
+    T: Iterator<Item = Token<'a>>,
+{
+    let selectors = ScopeSelectors::from_str(object.selectors()).ok()?;
+    let punctuation = ScopeSelectors::from_str("punctuation.definition").ok()?;
+    let lexemes: Vec<(Range, bool, bool)> = tokens
+        .filter_map(|token| match token {
+            Token::Lexeme(lexeme) => {
+                let end = Position {
+                    line: lexeme.position.line,
+                    offset: lexeme.position.offset + lexeme.value.graphemes(true).count(),
+                };
+                let scope = lexeme.scope.as_slice();
+
+                Some((
+                    Range::new(lexeme.position, end),
+                    selectors.does_match(scope).is_some(),
+                    punctuation.does_match(scope).is_some(),
+                ))
+            }
+            Token::Newline => None,
+        })
+        .collect();
+
+    // Prefer the lexeme under the cursor, falling back
+    // to the one it follows at the end of a line.
+    let index = lexemes
+        .iter()
+        .position(|(range, _, _)| range.start() <= *cursor && *cursor < range.end())
+        .or_else(|| {
+            lexemes
+                .iter()
+                .position(|(range, _, _)| range.end() == *cursor)
+        })?;
+    if !lexemes[index].1 {
+        return None;
+    }
+
+    let mut first = index;
+    while first > 0 && lexemes[first - 1].1 {
+        first -= 1;
+    }
+    let mut last = index;
+    while last + 1 < lexemes.len() && lexemes[last + 1].1 {
+        last += 1;
+    }
+
+    if object == SyntaxObject::Function {
+        let starts: Vec<Position> = lexemes[first..=last]
+            .iter()
+            .map(|(range, _, _)| range.start())
+            .collect();
+
+        return function(data, &starts, lexemes[last].0.end(), cursor, around);
+    }
+
+    if around {
+        return Some(Range::new(lexemes[first].0.start(), lexemes[last].0.end()));
+    }
+
+    while first < last && lexemes[first].2 {
+        first += 1;
+    }
+    while last > first && lexemes[last].2 {
+        last -= 1;
+    }
+    if lexemes[first].2 {
+        return Some(Range::new(lexemes[first].0.end(), lexemes[first].0.end()));
+    }
+
+    Some(Range::new(lexemes[first].0.start(), lexemes[last].0.end()))
+}
+
+// Neighbouring functions (e.g. separated by a blank line) can make up a
+// single run of lexemes, so the run, given by the start of each lexeme
+// and its end, is split after each function's body to find the one
+// around the cursor. Functions without a body extend to the run's end.
+fn function(
+    data: &str,
+    starts: &[Position],
+    end: Position,
+    cursor: &Position,
+    around: bool,
+) -> Option<Range> {
+    let graphemes = Graphemes::new(data);
+    let end_index = graphemes.index_of(&end)?;
+    let mut start = *starts.first()?;
+
+    loop {
+        let open =
+            (graphemes.index_of(&start)?..end_index).find(|&index| graphemes.text[index] == "{");
+        let Some(open) = open else {
+            return around.then(|| Range::new(start, end));
+        };
+        let body = delimited(&graphemes, open, '{', '}', true)?;
+
+        if *cursor < body.end() {
+            return if around {
+                Some(Range::new(start, body.end()))
+            } else {
+                delimited(&graphemes, open, '{', '}', false)
+            };
+        }
+        start = *starts.iter().find(|position| **position >= body.end())?;
+    }
+}
+
+// The buffer's graphemes alongside their positions,
+// ending with a position just past the last one.
+struct Graphemes<'a> {
+    text: Vec<&'a str>,
+    positions: Vec<Position>,
+}
+
+impl<'a> Graphemes<'a> {
+    fn new(data: &'a str) -> Graphemes<'a> {
+        let mut text = Vec::new();
+        let mut positions = Vec::new();
+        let mut position = Position { line: 0, offset: 0 };
+
+        for grapheme in data.graphemes(true) {
+            text.push(grapheme);
+            positions.push(position);
+
+            if is_newline(grapheme) {
+                position = Position {
+                    line: position.line + 1,
+                    offset: 0,
+                };
+            } else {
+                position.offset += 1;
+            }
+        }
+        positions.push(position);
+
+        Graphemes { text, positions }
+    }
+
+    fn index_of(&self, position: &Position) -> Option<usize> {
+        self.positions.iter().position(|p| p == position)
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range::new(self.positions[start], self.positions[end])
+    }
+}
+
+fn is_newline(grapheme: &str) -> bool {
+    grapheme == "\n" || grapheme == "\r\n"
+}
+
+#[derive(PartialEq)]
+enum Class {
+    Newline,
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+fn class(grapheme: &str) -> Class {
+    if is_newline(grapheme) {
+        Class::Newline
+    } else if grapheme.chars().all(char::is_whitespace) {
+        Class::Whitespace
+    } else if grapheme.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Class::Word
+    } else {
+        Class::Punctuation
+    }
+}
+
+fn word(graphemes: &Graphemes, index: usize, around: bool) -> Option<Range> {
+    let text = &graphemes.text;
+    let current_class = class(text.get(index)?);
+    if current_class == Class::Newline {
+        return None;
+    }
+
+    let mut start = index;
+    while start > 0 && class(text[start - 1]) == current_class {
+        start -= 1;
+    }
+    let mut end = index + 1;
+    while end < text.len() && class(text[end]) == current_class {
+        end += 1;
+    }
+
+    // Include trailing whitespace, or leading whitespace if there isn't any.
+    if around && current_class != Class::Whitespace {
+        let mut trailing_end = end;
+        while trailing_end < text.len() && class(text[trailing_end]) == Class::Whitespace {
+            trailing_end += 1;
+        }
+
+        if trailing_end > end {
+            end = trailing_end;
+        } else {
+            while start > 0 && class(text[start - 1]) == Class::Whitespace {
+                start -= 1;
+            }
+        }
+    }
+
+    Some(graphemes.range(start, end))
+}
+
+fn quoted(graphemes: &Graphemes, index: usize, quote: char, around: bool) -> Option<Range> {
+    let text = &graphemes.text;
+    let line = graphemes.positions[index].line;
+    let quote = quote.to_string();
+
+    // Quotes are paired up in order across the cursor's line, skipping escaped ones.
+    let quotes: Vec<usize> = (0..text.len())
+        .filter(|&i| graphemes.positions[i].line == line)
+        .filter(|&i| text[i] == quote && (i == 0 || text[i - 1] != "\\"))
+        .collect();
+    let (open, close) = quotes
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(open, close)| open <= index && index <= close)
+        .or_else(|| {
+            quotes
+                .chunks(2)
+                .filter(|pair| pair.len() == 2)
+                .map(|pair| (pair[0], pair[1]))
+                .find(|&(open, _)| open > index)
+        })?;
+
+    if around {
+        Some(graphemes.range(open, close + 1))
+    } else {
+        Some(graphemes.range(open + 1, close))
+    }
+}
+
+fn delimited(
+    graphemes: &Graphemes,
+    index: usize,
+    open: char,
+    close: char,
+    around: bool,
+) -> Option<Range> {
+    let text = &graphemes.text;
+    let open = open.to_string();
+    let close = close.to_string();
+
+    let start = if text.get(index) == Some(&open.as_str()) {
+        index
+    } else {
+        let mut depth = 0;
+        let mut start = None;
+        for i in (0..index).rev() {
+            if text[i] == close {
+                depth += 1;
+            } else if text[i] == open {
+                if depth == 0 {
+                    start = Some(i);
+                    break;
+                }
+                depth -= 1;
+            }
+        }
+        start?
+    };
+
+    let mut depth = 0;
+    let mut end = None;
+    for (i, grapheme) in text.iter().enumerate().skip(start + 1) {
+        if *grapheme == open {
+            depth += 1;
+        } else if *grapheme == close {
+            if depth == 0 {
+                end = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let end = end?;
+
+    if around {
+        return Some(graphemes.range(start, end + 1));
+    }
+
+    // Blocks spanning several lines leave their delimiters' lines intact.
+    let mut inner_start = start + 1;
+    let mut inner_end = end;
+    if inner_start < inner_end && is_newline(text[inner_start]) {
+        inner_start += 1;
+
+        let mut line_start = inner_end;
+        while line_start > inner_start && class(text[line_start - 1]) == Class::Whitespace {
+            line_start -= 1;
+        }
+        if line_start > inner_start && is_newline(text[line_start - 1]) {
+            inner_end = line_start;
+        }
+    }
+
+    Some(graphemes.range(inner_start, inner_end.max(inner_start)))
+}
+
+fn paragraph(data: &str, cursor: &Position, around: bool) -> Option<Range> {
+    let lines: Vec<&str> = data.split('\n').collect();
+    let blank = |line: usize| lines[line].trim().is_empty();
+    let current = cursor.line;
+    if current >= lines.len() {
+        return None;
+    }
+    let current_blank = blank(current);
+
+    let mut first = current;
+    while first > 0 && blank(first - 1) == current_blank {
+        first -= 1;
+    }
+    let mut last = current;
+    while last + 1 < lines.len() && blank(last + 1) == current_blank {
+        last += 1;
+    }
+
+    // Paragraphs include the blank lines after them,
+    // or those before them when at the end of the buffer.
+    if around {
+        let mut following = last;
+        while following + 1 < lines.len() && blank(following + 1) != current_blank {
+            following += 1;
+        }
+
+        if following > last {
+            last = following;
+        } else {
+            while first > 0 && blank(first - 1) != current_blank {
+                first -= 1;
+            }
+        }
+    }
+
+    let end = if last + 1 < lines.len() {
+        Position {
+            line: last + 1,
+            offset: 0,
+        }
+    } else {
+        Position {
+            line: last,
+            offset: lines[last].graphemes(true).count(),
+        }
+    };
+
+    Some(Range::new(
+        Position {
+            line: first,
+            offset: 0,
+        },
+        end,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, find_syntax_object, SyntaxObject, TextObject};
+    use scribe::buffer::{Position, Range};
+    use scribe::Workspace;
+    use std::path::Path;
+
+    fn range(start: (usize, usize), end: (usize, usize)) -> Range {
+        Range::new(
+            Position {
+                line: start.0,
+                offset: start.1,
+            },
+            Position {
+                line: end.0,
+                offset: end.1,
+            },
+        )
+    }
+
+    #[test]
+    fn word_objects_include_trailing_whitespace_when_around() {
+        let cursor = Position { line: 0, offset: 5 };
+
+        assert_eq!(
+            find("one two three", &cursor, TextObject::Word, false),
+            Some(range((0, 4), (0, 7)))
+        );
+        assert_eq!(
+            find("one two three", &cursor, TextObject::Word, true),
+            Some(range((0, 4), (0, 8)))
+        );
+    }
+
+    #[test]
+    fn quoted_objects_use_the_pair_around_or_after_the_cursor() {
+        let data = "say(\"a \\\"b\\\"\", 'c')";
+
+        assert_eq!(
+            find(
+                data,
+                &Position { line: 0, offset: 6 },
+                TextObject::Quoted('"'),
+                false
+            ),
+            Some(range((0, 5), (0, 12)))
+        );
+        assert_eq!(
+            find(
+                data,
+                &Position { line: 0, offset: 0 },
+                TextObject::Quoted('\''),
+                true
+            ),
+            Some(range((0, 15), (0, 18)))
+        );
+    }
+
+    #[test]
+    fn delimited_objects_skip_nested_pairs() {
+        let data = "f(a, (b), c)";
+        let object = TextObject::Delimited('(', ')');
+
+        assert_eq!(
+            find(
+                data,
+                &Position {
+                    line: 0,
+                    offset: 10
+                },
+                object,
+                false
+            ),
+            Some(range((0, 2), (0, 11)))
+        );
+        assert_eq!(
+            find(data, &Position { line: 0, offset: 6 }, object, true),
+            Some(range((0, 5), (0, 8)))
+        );
+    }
+
+    #[test]
+    fn inner_delimited_objects_spanning_lines_keep_the_delimiter_lines() {
+        let data = "fn x() {\n    body\n}";
+
+        assert_eq!(
+            find(
+                data,
+                &Position { line: 1, offset: 4 },
+                TextObject::Delimited('{', '}'),
+                false
+            ),
+            Some(range((1, 0), (2, 0)))
+        );
+    }
+
+    #[test]
+    fn paragraph_objects_include_following_blank_lines_when_around() {
+        let data = "one\ntwo\n\n\nthree";
+        let cursor = Position { line: 1, offset: 0 };
+
+        assert_eq!(
+            find(data, &cursor, TextObject::Paragraph, false),
+            Some(range((0, 0), (2, 0)))
+        );
+        assert_eq!(
+            find(data, &cursor, TextObject::Paragraph, true),
+            Some(range((0, 0), (4, 0)))
+        );
+    }
+
+    #[test]
+    fn syntax_objects_use_the_scopes_around_the_cursor() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let data = "fn main() {\n    let x = \"nexedit\";\n}\n";
+        let mut buffer = scribe::Buffer::new();
+        buffer.insert(data);
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        workspace.add_buffer(buffer);
+        let tokens = workspace.current_buffer_tokens().unwrap();
+        let cursor = Position {
+            line: 1,
+            offset: 15,
+        };
+
+        assert_eq!(
+            find_syntax_object(
+                tokens.iter().unwrap(),
+                data,
+                &cursor,
+                SyntaxObject::String,
+                false
+            ),
+            Some(range((1, 13), (1, 20)))
+        );
+        assert_eq!(
+            find_syntax_object(
+                tokens.iter().unwrap(),
+                data,
+                &cursor,
+                SyntaxObject::String,
+                true
+            ),
+            Some(range((1, 12), (1, 21)))
+        );
+        assert_eq!(
+            find_syntax_object(
+                tokens.iter().unwrap(),
+                data,
+                &cursor,
+                SyntaxObject::Function,
+                false
+            ),
+            Some(range((1, 0), (2, 0)))
+        );
+    }
+
+    #[test]
+    fn function_objects_stop_at_the_end_of_the_function() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let data = "fn a() {\n    x();\n}\n\nfn b() {\n    y();\n}\n";
+        let mut buffer = scribe::Buffer::new();
+        buffer.insert(data);
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        workspace.add_buffer(buffer);
+        let tokens = workspace.current_buffer_tokens().unwrap();
+        let first = Position { line: 1, offset: 4 };
+        let second = Position { line: 5, offset: 4 };
+
+        assert_eq!(
+            find_syntax_object(
+                tokens.iter().unwrap(),
+                data,
+                &first,
+                SyntaxObject::Function,
+                true
+            ),
+            Some(range((0, 0), (2, 1)))
+        );
+        assert_eq!(
+            find_syntax_object(
+                tokens.iter().unwrap(),
+                data,
+                &second,
+                SyntaxObject::Function,
+                true
+            ),
+            Some(range((4, 0), (6, 1)))
+        );
+        assert_eq!(
+            find_syntax_object(
+                tokens.iter().unwrap(),
+                data,
+                &second,
+                SyntaxObject::Function,
+                false
+            ),
+            Some(range((5, 0), (6, 0)))
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use scribe::buffer::LineRange;
+
+/// A buffer's folded line ranges. The first line of a fold remains visible,
+/// standing in for the rest, which are hidden.
+#[derive(Default)]
+pub struct Folds {
+    ranges: Vec<LineRange>,
+    line_count: usize,
+    changed_lines: Vec<usize>,
+}
+
+impl Folds {
+    pub fn new() -> Folds {
+        Folds::default()
+    }
+
+    /// Adds a fold, replacing any it overlaps.
+    pub fn add(&mut self, range: LineRange, line_count: usize) {
+        self.reconcile(line_count);
+        self.ranges
+            .retain(|fold| fold.end() <= range.start() || range.end() <= fold.start());
+        self.ranges.push(range);
+        self.ranges.sort_by_key(|fold| fold.start());
+    }
+
+    /// Removes the fold including the specified line, if there is one.
+    pub fn remove(&mut self, line: usize, line_count: usize) -> bool {
+        self.reconcile(line_count);
+        let count = self.ranges.len();
+        self.ranges.retain(|fold| !fold.includes(line));
+
+        self.ranges.len() < count
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+        self.changed_lines.clear();
+    }
+
+    pub fn ranges(&mut self, line_count: usize) -> &[LineRange] {
+        self.reconcile(line_count);
+
+        &self.ranges
+    }
+
+    /// Tracks a buffer change, so that folds can be updated once
+    /// the buffer's resulting line count is known.
+    pub fn invalidate_from(&mut self, line: usize) {
+        self.changed_lines.push(line);
+    }
+
+    // Shifts folds following a single change by the number of lines it
+    // added or removed, dropping those it touched. There's no telling how
+    // lines moved between several changes, so folds after the first are
+    // dropped in that case.
+    fn reconcile(&mut self, line_count: usize) {
+        let delta = line_count as isize - self.line_count as isize;
+        self.line_count = line_count;
+
+        let Some(&first_change) = self.changed_lines.iter().min() else {
+            return;
+        };
+        let single_change = self.changed_lines.len() == 1;
+        self.changed_lines.clear();
+
+        self.ranges = self
+            .ranges
+            .iter()
+            .filter_map(|fold| {
+                if fold.end() <= first_change || (fold.start() == first_change && delta == 0) {
+                    return Some(LineRange::new(fold.start(), fold.end()));
+                }
+                if !single_change || fold.start() <= first_change {
+                    return None;
+                }
+
+                let start = fold.start() as isize + delta;
+                if start <= first_change as isize {
+                    return None;
+                }
+
+                Some(LineRange::new(
+                    start as usize,
+                    (fold.end() as isize + delta) as usize,
+                ))
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Folds;
+    use crate::util::fold::hidden;
+    use scribe::buffer::LineRange;
+
+    #[test]
+    fn add_replaces_overlapping_folds() {
+        let mut folds = Folds::new();
+        folds.add(LineRange::new(2, 4), 10);
+        folds.add(LineRange::new(6, 8), 10);
+        folds.add(LineRange::new(1, 5), 10);
+
+        assert_eq!(
+            folds.ranges(10),
+            &[LineRange::new(1, 5), LineRange::new(6, 8)]
+        );
+        assert!(hidden(folds.ranges(10), 4));
+        assert!(!hidden(folds.ranges(10), 5));
+        assert!(!hidden(folds.ranges(10), 6));
+    }
+
+    #[test]
+    fn ranges_shifts_folds_following_a_change() {
+        let mut folds = Folds::new();
+        folds.add(LineRange::new(0, 2), 10);
+        folds.add(LineRange::new(3, 5), 10);
+        folds.add(LineRange::new(6, 8), 10);
+
+        // Two lines inserted on the second fold's first line.
+        folds.invalidate_from(3);
+        assert_eq!(
+            folds.ranges(12),
+            &[LineRange::new(0, 2), LineRange::new(8, 10)]
+        );
+    }
+
+    #[test]
+    fn ranges_drops_folds_following_several_changes() {
+        let mut folds = Folds::new();
+        folds.add(LineRange::new(0, 2), 10);
+        folds.add(LineRange::new(6, 8), 10);
+
+        folds.invalidate_from(4);
+        folds.invalidate_from(2);
+        assert_eq!(folds.ranges(10), &[LineRange::new(0, 2)]);
+    }
+}
@@ -1,3 +1,4 @@
+mod folds;
 mod lexeme_mapper;
 mod line_numbers;
 mod render_cache;
@@ -5,6 +6,7 @@ mod render_state;
 mod renderer;
 mod scrollable_region;
 
+pub use self::folds::Folds;
 pub use self::lexeme_mapper::{LexemeMapper, MappedLexeme};
 pub use self::line_numbers::LineNumbers;
 pub use self::render_cache::RenderCache;
@@ -1,11 +1,13 @@
 use crate::errors::*;
 use crate::models::application::Preferences;
+use crate::util::brackets::{self, Bracket};
+use crate::util::fold;
 use crate::view::buffer::line_numbers::*;
 use crate::view::buffer::{LexemeMapper, MappedLexeme, RenderState};
 use crate::view::color::to_rgb_color;
-use crate::view::terminal::{Cell, Terminal, TerminalBuffer};
+use crate::view::terminal::{Cell, TerminalBuffer};
 use crate::view::{Colors, RGBColor, Style, RENDER_CACHE_FREQUENCY};
-use scribe::buffer::{Buffer, Position, Range};
+use scribe::buffer::{Buffer, LineRange, Position, Range};
 use scribe::util::LineIterator;
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -18,31 +20,40 @@ use syntect::parsing::{ScopeStack, SyntaxSet};
 use unicode_segmentation::UnicodeSegmentation;
 
 pub struct BufferRenderer<'a, 'p> {
+    brackets: Vec<(Bracket, Position)>,
     buffer: &'a Buffer,
     buffer_position: Position,
     cursor_position: Option<Position>,
+    folds: &'a [LineRange],
     gutter_width: usize,
     highlights: Option<&'a [Range]>,
+    secondary_cursors: &'a [Position],
     stylist: Highlighter<'a>,
     current_style: ThemeStyle,
+    line_brackets: Vec<usize>,
     line_numbers: LineNumbers,
+    line_offset: usize,
     preferences: &'a Preferences,
     render_cache: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
     screen_position: Position,
     scroll_offset: usize,
     syntax_set: &'a SyntaxSet,
-    terminal: &'a dyn Terminal,
     terminal_buffer: &'a mut TerminalBuffer<'p>,
     theme: &'a Theme,
+    width: usize,
+    height: usize,
 }
 
 impl<'a, 'p> BufferRenderer<'a, 'p> {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         buffer: &'a Buffer,
+        folds: &'a [LineRange],
         highlights: Option<&'a [Range]>,
+        secondary_cursors: &'a [Position],
         scroll_offset: usize,
-        terminal: &'a dyn Terminal,
+        width: usize,
+        height: usize,
         theme: &'a Theme,
         preferences: &'a Preferences,
         render_cache: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
@@ -56,22 +67,28 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         let current_style = stylist.get_default();
 
         BufferRenderer {
+            brackets: Vec::new(),
             buffer,
             cursor_position: None,
+            folds,
             gutter_width,
             highlights,
+            secondary_cursors,
             stylist,
             current_style,
+            line_brackets: Vec::new(),
             line_numbers,
+            line_offset: 0,
             buffer_position: Position { line: 0, offset: 0 },
             preferences,
             render_cache,
             screen_position: Position { line: 0, offset: 0 },
             scroll_offset,
             syntax_set,
-            terminal,
             terminal_buffer,
             theme,
+            width,
+            height,
         }
     }
 
@@ -79,23 +96,34 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         self.buffer_position.line == self.buffer.cursor.line
     }
 
+    fn on_secondary_cursor(&self) -> bool {
+        self.secondary_cursors.contains(&self.buffer_position)
+    }
+
     fn print_rest_of_line(&mut self) {
         let on_cursor_line = self.on_cursor_line();
         let guide_offsets = self.length_guide_offsets();
 
-        for offset in self.screen_position.offset..self.terminal.width() {
+        let on_secondary_cursor = self.on_secondary_cursor();
+
+        for offset in self.screen_position.offset..self.width {
             let colors = if on_cursor_line || guide_offsets.contains(&offset) {
                 Colors::Focused
             } else {
                 Colors::Default
             };
+            let style = if on_secondary_cursor && offset == self.screen_position.offset {
+                Style::Inverted
+            } else {
+                Style::Default
+            };
 
             self.print(
                 Position {
                     line: self.screen_position.line,
                     offset,
                 },
-                Style::Default,
+                style,
                 colors,
                 " ",
             );
@@ -110,9 +138,14 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             .collect()
     }
 
+    fn on_hidden_line(&self) -> bool {
+        fold::hidden(self.folds, self.buffer_position.line)
+    }
+
     fn advance_to_next_line(&mut self) {
-        if self.inside_visible_content() {
+        if self.inside_visible_content() && !self.on_hidden_line() {
             self.set_cursor();
+            self.print_fold_summary();
             self.print_rest_of_line();
 
             self.screen_position.line += 1;
@@ -131,6 +164,10 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     }
 
     fn current_char_style(&self, token_color: RGBColor) -> (Style, Colors) {
+        if self.on_secondary_cursor() {
+            return (Style::Inverted, Colors::Default);
+        }
+
         let (style, colors) = match self.highlights {
             Some(highlight_ranges) => {
                 for range in highlight_ranges {
@@ -163,6 +200,8 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
 
     fn print_lexeme<L: Into<Cow<'p, str>>>(&mut self, lexeme: L) {
         for character in lexeme.into().graphemes(true) {
+            let line_offset = self.line_offset;
+            self.line_offset += character.len();
             if character == "\n" {
                 continue;
             }
@@ -173,7 +212,7 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             let (style, color) = self.current_char_style(token_color);
 
             if self.preferences.line_wrapping()
-                && self.screen_position.offset == self.terminal.width()
+                && self.screen_position.offset == self.width
             {
                 self.screen_position.line += 1;
                 self.screen_position.offset = self.gutter_width;
@@ -185,8 +224,8 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                     self.next_tab_stop(self.screen_position.offset - self.gutter_width);
                 let mut screen_tab_stop = buffer_tab_stop + self.gutter_width;
 
-                if screen_tab_stop > self.terminal.width() {
-                    screen_tab_stop = self.terminal.width();
+                if screen_tab_stop > self.width {
+                    screen_tab_stop = self.width;
                 }
 
                 for _ in self.screen_position.offset..screen_tab_stop {
@@ -200,16 +239,86 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                 self.buffer_position.offset += 1;
             }
 
+            // Track where brackets were printed, so that
+            // the one matching the cursor can be highlighted.
+            if self.line_brackets.contains(&line_offset) {
+                if let Some(bracket) = brackets::bracket_char(character) {
+                    self.brackets.push((
+                        Bracket {
+                            position: Position {
+                                line: self.buffer_position.line,
+                                offset: self.buffer_position.offset - 1,
+                            },
+                            character: bracket,
+                        },
+                        Position {
+                            line: self.screen_position.line,
+                            offset: self.screen_position.offset - 1,
+                        },
+                    ));
+                }
+            }
+
             self.set_cursor();
         }
     }
 
+    // Stands in for a fold's hidden lines, following its first line.
+    fn print_fold_summary(&mut self) {
+        let Some(fold) = self
+            .folds
+            .iter()
+            .find(|fold| fold.start() == self.buffer_position.line)
+        else {
+            return;
+        };
+
+        let (_, blurred_style) = self.mapper_styles();
+        let colors = if self.on_cursor_line() {
+            Colors::CustomFocusedForeground(to_rgb_color(blurred_style.foreground))
+        } else {
+            Colors::CustomForeground(to_rgb_color(blurred_style.foreground))
+        };
+        let summary = format!(" ... {} lines", fold.end() - fold.start());
+
+        for character in summary.chars() {
+            if self.screen_position.offset >= self.width {
+                break;
+            }
+
+            self.print(self.screen_position, Style::Default, colors, character.to_string());
+            self.screen_position.offset += 1;
+        }
+    }
+
+    // Brackets are only tracked on-screen, so this finds a match whenever both
+    // halves are visible, since everything between them must be visible too.
+    fn highlight_matching_bracket(&mut self) {
+        let brackets: Vec<Bracket> = self.brackets.iter().map(|(bracket, _)| *bracket).collect();
+        let Some(index) = brackets
+            .iter()
+            .position(|bracket| bracket.position == *self.buffer.cursor)
+        else {
+            return;
+        };
+
+        if let Some(match_index) = brackets::find_match(&brackets, index) {
+            let (bracket, screen_position) = self.brackets[match_index];
+            self.print(
+                screen_position,
+                Style::Inverted,
+                Colors::Default,
+                bracket.character.to_string(),
+            );
+        }
+    }
+
     fn before_visible_content(&mut self) -> bool {
         self.buffer_position.line < self.scroll_offset
     }
 
     fn after_visible_content(&self) -> bool {
-        self.screen_position.line >= (self.terminal.height() - 1)
+        self.screen_position.line >= (self.height - 1)
     }
 
     fn inside_visible_content(&mut self) -> bool {
@@ -221,7 +330,6 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         lines: LineIterator<'p>,
         mut lexeme_mapper: Option<&mut dyn LexemeMapper>,
     ) -> Result<Option<Position>> {
-        self.terminal.set_cursor(None);
         self.print_line_number();
 
         let highlighter = Highlighter::new(self.theme);
@@ -248,11 +356,22 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                     .parse
                     .parse_line(line, self.syntax_set)
                     .chain_err(|| BUFFER_PARSE_FAILED)?;
+                self.line_offset = 0;
+                let hidden = self.on_hidden_line();
+                self.line_brackets = if lexeme_mapper.is_none()
+                    && !self.before_visible_content()
+                    && !hidden
+                {
+                    brackets::line_offsets(line, state.highlight.path.clone(), &events)
+                } else {
+                    Vec::new()
+                };
                 let styled_lexemes =
                     HighlightIterator::new(&mut state.highlight, &events, line, &highlighter);
 
                 for (style, lexeme) in styled_lexemes {
-                    if self.before_visible_content() {
+                    // Folded lines are still parsed, to keep the syntax state current.
+                    if self.before_visible_content() || hidden {
                         continue;
                     }
 
@@ -289,6 +408,7 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         self.set_cursor();
 
         self.print_rest_of_line();
+        self.highlight_matching_bracket();
 
         Ok(self.cursor_position)
     }
@@ -299,6 +419,9 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         };
 
         let line_number = self.line_numbers.next().unwrap();
+        if self.on_hidden_line() {
+            return;
+        }
 
         let weight = if self.on_cursor_line() {
             Style::Bold
@@ -387,7 +510,8 @@ mod tests {
     use super::{BufferRenderer, LexemeMapper, MappedLexeme};
     use crate::models::application::Preferences;
     use crate::view::terminal::*;
-    use scribe::buffer::Position;
+    use crate::view::Style;
+    use scribe::buffer::{LineRange, Position};
     use scribe::util::LineIterator;
     use scribe::{Buffer, Workspace};
     use std::cell::RefCell;
@@ -418,9 +542,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             0,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &Rc::new(RefCell::new(HashMap::new())),
@@ -452,9 +579,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             0,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &Rc::new(RefCell::new(HashMap::new())),
@@ -492,9 +622,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             0,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &Rc::new(RefCell::new(HashMap::new())),
@@ -511,6 +644,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_highlights_the_bracket_matching_the_cursor() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("f(\")\", x)");
+        buffer.syntax_definition = workspace.syntax_set.find_syntax_by_extension("rs").cloned();
+        buffer.cursor.move_to(Position { line: 0, offset: 1 });
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer.as_ref().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+
+        BufferRenderer::new(
+            workspace.current_buffer.as_ref().unwrap(),
+            &[],
+            None,
+            &[],
+            0,
+            terminal.width(),
+            terminal.height(),
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &workspace.syntax_set,
+            &mut terminal_buffer,
+        )
+        .render(lines, None)
+        .unwrap();
+
+        let inverted_cells: Vec<Position> = terminal_buffer
+            .iter()
+            .filter(|(_, cell)| cell.style == Style::Inverted)
+            .map(|(position, _)| position)
+            .collect();
+        assert_eq!(inverted_cells, vec![Position { line: 1, offset: 6 }]);
+    }
+
+    #[test]
+    fn render_replaces_folded_lines_with_a_summary() {
+        let mut workspace = Workspace::new(Path::new("."), None).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("a\n b\n c\nd\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer.as_ref().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+
+        BufferRenderer::new(
+            workspace.current_buffer.as_ref().unwrap(),
+            &[LineRange::new(0, 3)],
+            None,
+            &[],
+            0,
+            terminal.width(),
+            terminal.height(),
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &workspace.syntax_set,
+            &mut terminal_buffer,
+        )
+        .render(lines, None)
+        .unwrap();
+
+        let expected_content = " 1  a ... \n 4  d     \n 5        ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
     #[test]
     fn render_wraps_lines_correctly() {
         let mut workspace = Workspace::new(Path::new("."), None).unwrap();
@@ -527,9 +739,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             0,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &Rc::new(RefCell::new(HashMap::new())),
@@ -569,9 +784,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             0,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &Rc::new(RefCell::new(HashMap::new())),
@@ -604,9 +822,12 @@ mod tests {
 
         let cursor_position = BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             0,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &Rc::new(RefCell::new(HashMap::new())),
@@ -639,9 +860,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             495,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &render_cache,
@@ -675,9 +899,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             95,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &render_cache,
@@ -697,9 +924,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             495,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &render_cache,
@@ -736,9 +966,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             95,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &render_cache,
@@ -757,9 +990,12 @@ mod tests {
 
         BufferRenderer::new(
             workspace.current_buffer.as_ref().unwrap(),
+            &[],
             None,
+            &[],
             200,
-            &**terminal,
+            terminal.width(),
+            terminal.height(),
             &theme_set.themes["base16-ocean.dark"],
             &preferences,
             &render_cache,
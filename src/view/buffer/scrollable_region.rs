@@ -1,28 +1,35 @@
+use crate::util::fold;
 use crate::view::buffer::LineNumbers;
-use crate::view::terminal::Terminal;
-use scribe::buffer::Buffer;
-use std::sync::Arc;
+use scribe::buffer::{Buffer, LineRange};
 use unicode_segmentation::UnicodeSegmentation;
 
 pub struct ScrollableRegion {
-    terminal: Arc<Box<dyn Terminal + Sync + Send + 'static>>,
+    width: usize,
+    height: usize,
     line_offset: usize,
 }
 
 impl ScrollableRegion {
-    pub fn new(terminal: Arc<Box<dyn Terminal + Sync + Send + 'static>>) -> ScrollableRegion {
+    pub fn new(width: usize, height: usize) -> ScrollableRegion {
         ScrollableRegion {
-            terminal,
+            width,
+            height,
             line_offset: 0,
         }
     }
 
-    pub fn scroll_into_view(&mut self, buffer: &Buffer) {
+    /// Updates the region's dimensions, including its status line.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn scroll_into_view(&mut self, buffer: &Buffer, folds: &[LineRange]) {
         if buffer.cursor.line <= self.line_offset {
             self.line_offset = buffer.cursor.line;
         } else {
             let starting_line = (buffer.cursor.line)
-                .saturating_sub(self.preceding_line_count(buffer, self.height()));
+                .saturating_sub(self.preceding_line_count(buffer, folds, self.height()));
 
             if starting_line > self.line_offset {
                 self.line_offset = starting_line;
@@ -30,13 +37,13 @@ impl ScrollableRegion {
         }
     }
 
-    pub fn scroll_to_center(&mut self, buffer: &Buffer) {
+    pub fn scroll_to_center(&mut self, buffer: &Buffer, folds: &[LineRange]) {
         let limit = (self.height() as f32 / 2.0).ceil() as usize;
 
         self.line_offset = buffer
             .cursor
             .line
-            .saturating_sub(self.preceding_line_count(buffer, limit));
+            .saturating_sub(self.preceding_line_count(buffer, folds, limit));
     }
 
     pub fn line_offset(&self) -> usize {
@@ -52,26 +59,40 @@ impl ScrollableRegion {
     }
 
     fn height(&self) -> usize {
-        self.terminal.height() - 1
+        self.height - 1
     }
 
-    fn preceding_line_count(&self, buffer: &Buffer, limit: usize) -> usize {
+    fn preceding_line_count(&self, buffer: &Buffer, folds: &[LineRange], limit: usize) -> usize {
         let mut preceding_line_count = 0;
 
         let gutter_width = LineNumbers::new(buffer, None).width() + 1;
 
+        // Folded lines aren't drawn, so look far
+        // enough back to find enough visible ones.
         let end = buffer.cursor.line + 1;
-        let start = end.saturating_sub(limit);
+        let mut start = end;
+        let mut visible_line_count = 0;
+        while start > 0 && visible_line_count < limit {
+            start -= 1;
+            if !fold::hidden(folds, start) {
+                visible_line_count += 1;
+            }
+        }
         let line_count = end - start;
 
         let visual_line_counts: Vec<usize> = buffer
             .data()
             .lines()
+            .enumerate()
             .skip(start)
             .take(line_count)
-            .map(|line| {
+            .map(|(line_number, line)| {
+                if fold::hidden(folds, line_number) {
+                    return 0;
+                }
+
                 let grapheme_count = line.graphemes(true).count().max(1) as f32;
-                let buffer_content_width = (self.terminal.width() - gutter_width) as f32;
+                let buffer_content_width = (self.width - gutter_width) as f32;
                 let wrapped_line_count = grapheme_count / buffer_content_width;
 
                 wrapped_line_count.ceil() as usize
@@ -107,10 +128,10 @@ mod tests {
     fn scroll_into_view_correctly_handles_additonal_rendered_trailing_newline() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         buffer.insert("\n\n");
         buffer.cursor.move_to(Position { line: 2, offset: 0 });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 0);
     }
 
@@ -118,7 +139,7 @@ mod tests {
     fn scroll_into_view_correctly_handles_additonal_rendered_trailing_newline_at_edge_of_region() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         for _ in 0..10 {
             buffer.insert("\n");
         }
@@ -126,7 +147,7 @@ mod tests {
             line: 10,
             offset: 0,
         });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 2);
     }
 
@@ -134,12 +155,12 @@ mod tests {
     fn scroll_into_view_advances_region_if_line_after_current_range() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         for _ in 0..10 {
             buffer.insert("word \n");
         }
         buffer.cursor.move_to(Position { line: 9, offset: 0 });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 1);
     }
 
@@ -147,13 +168,13 @@ mod tests {
     fn scroll_into_view_recedes_region_if_line_before_current_range() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         region.scroll_down(10);
         for _ in 0..5 {
             buffer.insert("\n");
         }
         buffer.cursor.move_to(Position { line: 5, offset: 0 });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 5);
     }
 
@@ -161,12 +182,12 @@ mod tests {
     fn scroll_into_view_considers_empty_lines_when_deciding_to_advance_region() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         for _ in 0..10 {
             buffer.insert("\n");
         }
         buffer.cursor.move_to(Position { line: 9, offset: 0 });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 1);
     }
 
@@ -174,14 +195,14 @@ mod tests {
     fn scroll_into_view_advances_line_offset_if_preceding_lines_wrap() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         buffer.insert("cursor");
         for _ in 0..5 {
             buffer.insert("       \n");
         }
 
         buffer.cursor.move_to(Position { line: 5, offset: 0 });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 1);
     }
 
@@ -189,13 +210,13 @@ mod tests {
     fn scroll_into_view_advances_line_offset_if_cursor_line_and_preceding_lines_wrap() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         buffer.insert("cursor line\n");
         for _ in 0..5 {
             buffer.insert("       \n");
         }
         buffer.cursor.move_to(Position { line: 5, offset: 0 });
-        region.scroll_into_view(&buffer);
+        region.scroll_into_view(&buffer, &[]);
         assert_eq!(region.line_offset(), 2);
     }
 
@@ -203,7 +224,7 @@ mod tests {
     fn scroll_to_center_sets_correct_line_offset() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         for _ in 0..20 {
             buffer.insert("\n");
         }
@@ -211,7 +232,7 @@ mod tests {
             line: 20,
             offset: 0,
         });
-        region.scroll_to_center(&buffer);
+        region.scroll_to_center(&buffer, &[]);
         assert_eq!(region.line_offset(), 16);
     }
 
@@ -219,8 +240,8 @@ mod tests {
     fn scroll_to_center_does_not_set_negative_offset() {
         let terminal = build_terminal().unwrap();
         let buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
-        region.scroll_to_center(&buffer);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
+        region.scroll_to_center(&buffer, &[]);
         assert_eq!(region.line_offset(), 0);
     }
 
@@ -228,7 +249,7 @@ mod tests {
     fn scroll_to_center_weighs_wrapped_lines_correctly() {
         let terminal = build_terminal().unwrap();
         let mut buffer = Buffer::new();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         for _ in 0..4 {
             buffer.insert("       \n");
         }
@@ -236,7 +257,7 @@ mod tests {
         for _ in 0..6 {
             buffer.insert("\n");
         }
-        region.scroll_to_center(&buffer);
+        region.scroll_to_center(&buffer, &[]);
         assert_eq!(region.line_offset(), 2);
     }
 
@@ -248,15 +269,15 @@ mod tests {
             buffer.insert("\n");
         }
         buffer.cursor.move_to(Position { line: 5, offset: 0 });
-        let mut region = ScrollableRegion::new(terminal);
-        region.scroll_to_center(&buffer);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
+        region.scroll_to_center(&buffer, &[]);
         assert_eq!(region.line_offset(), 1);
     }
 
     #[test]
     fn scroll_down_increases_line_offset_by_amount() {
         let terminal = build_terminal().unwrap();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         region.scroll_down(10);
         assert_eq!(region.line_offset(), 10);
     }
@@ -264,7 +285,7 @@ mod tests {
     #[test]
     fn scroll_up_decreases_line_offset_by_amount() {
         let terminal = build_terminal().unwrap();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         region.scroll_down(10);
         region.scroll_up(5);
         assert_eq!(region.line_offset(), 5);
@@ -273,7 +294,7 @@ mod tests {
     #[test]
     fn scroll_up_does_not_scroll_beyond_top_of_region() {
         let terminal = build_terminal().unwrap();
-        let mut region = ScrollableRegion::new(terminal);
+        let mut region = ScrollableRegion::new(terminal.width(), terminal.height());
         region.scroll_up(5);
         assert_eq!(region.line_offset(), 0);
     }
@@ -0,0 +1,481 @@
+use scribe::buffer::Position;
+use std::cmp;
+use std::collections::HashMap;
+use std::mem;
+
+// Limits on how much of a split either half can take up, as a percentage.
+const MIN_RATIO: isize = 10;
+const MAX_RATIO: isize = 90;
+
+/// How the two halves of a split are arranged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arrangement {
+    SideBySide,
+    Stacked,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A rectangular area of the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub origin: Position,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    // Divides the area in two, giving the first half the specified percentage of it.
+    fn split(&self, arrangement: Arrangement, ratio: usize) -> (Rect, Rect) {
+        match arrangement {
+            Arrangement::SideBySide => {
+                let width = self.width * ratio / 100;
+
+                (
+                    Rect { width, ..*self },
+                    Rect {
+                        origin: Position {
+                            line: self.origin.line,
+                            offset: self.origin.offset + width,
+                        },
+                        width: self.width - width,
+                        height: self.height,
+                    },
+                )
+            }
+            Arrangement::Stacked => {
+                let height = self.height * ratio / 100;
+
+                (
+                    Rect { height, ..*self },
+                    Rect {
+                        origin: Position {
+                            line: self.origin.line + height,
+                            offset: self.origin.offset,
+                        },
+                        width: self.width,
+                        height: self.height - height,
+                    },
+                )
+            }
+        }
+    }
+
+    fn lines(&self) -> (usize, usize) {
+        (self.origin.line, self.origin.line + self.height)
+    }
+
+    fn offsets(&self) -> (usize, usize) {
+        (self.origin.offset, self.origin.offset + self.width)
+    }
+}
+
+/// The buffer shown in a pane and, while the pane
+/// isn't focused, where its cursor was left.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pane {
+    pub buffer_id: Option<usize>,
+    pub cursor: Position,
+    pub line_count: usize,
+}
+
+enum Node {
+    Pane(usize),
+    Split {
+        arrangement: Arrangement,
+        ratio: usize,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+/// A tree of panes, built by repeatedly splitting them in two.
+pub struct Layout {
+    root: Node,
+    panes: HashMap<usize, Pane>,
+    focused: usize,
+    next_id: usize,
+}
+
+impl Layout {
+    pub fn new() -> Layout {
+        Layout {
+            root: Node::Pane(0),
+            panes: HashMap::from([(0, Pane::default())]),
+            focused: 0,
+            next_id: 1,
+        }
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn focus(&mut self, id: usize) -> bool {
+        if self.panes.contains_key(&id) {
+            self.focused = id;
+        }
+
+        self.focused == id
+    }
+
+    pub fn pane(&self, id: usize) -> Option<&Pane> {
+        self.panes.get(&id)
+    }
+
+    pub fn pane_mut(&mut self, id: usize) -> Option<&mut Pane> {
+        self.panes.get_mut(&id)
+    }
+
+    pub fn panes(&self) -> impl Iterator<Item = (&usize, &Pane)> {
+        self.panes.iter()
+    }
+
+    /// Moves the cursors of unfocused panes showing the buffer so that they
+    /// follow their content when lines after the changed line are added or
+    /// removed. Panes without a line count to compare against are left alone.
+    pub fn shift_cursors(
+        &mut self,
+        buffer_id: usize,
+        changed_line: Option<usize>,
+        line_count: usize,
+    ) {
+        for (id, pane) in self.panes.iter_mut() {
+            if *id == self.focused || pane.buffer_id != Some(buffer_id) {
+                continue;
+            }
+
+            let shifted =
+                changed_line.filter(|line| pane.line_count > 0 && pane.cursor.line > *line);
+            if let Some(changed_line) = shifted {
+                let line =
+                    pane.cursor.line as isize + line_count as isize - pane.line_count as isize;
+                pane.cursor.line = cmp::max(line, changed_line as isize) as usize;
+            }
+            pane.line_count = line_count;
+        }
+    }
+
+    /// Splits the focused pane in two, showing its
+    /// buffer in the new half, which is then focused.
+    pub fn split(&mut self, arrangement: Arrangement) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let pane = self.panes.get(&self.focused).copied().unwrap_or_default();
+        self.panes.insert(id, pane);
+
+        if let Some(node) = find(&mut self.root, self.focused) {
+            *node = Node::Split {
+                arrangement,
+                ratio: 50,
+                first: Box::new(Node::Pane(self.focused)),
+                second: Box::new(Node::Pane(id)),
+            };
+        }
+        self.focused = id;
+
+        id
+    }
+
+    /// Closes the focused pane, handing its space (and focus) to the
+    /// neighbouring half of its split. The last pane can't be closed.
+    pub fn close(&mut self) -> bool {
+        match remove(&mut self.root, self.focused) {
+            Some(focused) => {
+                self.panes.remove(&self.focused);
+                self.focused = focused;
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Grows (or, given a negative amount, shrinks) the focused pane
+    /// by a percentage of the split it's in.
+    pub fn resize(&mut self, amount: isize) -> bool {
+        resize(&mut self.root, self.focused, amount)
+    }
+
+    /// The screen area occupied by each pane.
+    pub fn areas(&self, width: usize, height: usize) -> Vec<(usize, Rect)> {
+        let mut areas = Vec::new();
+        collect_areas(
+            &self.root,
+            Rect {
+                origin: Position::default(),
+                width,
+                height,
+            },
+            &mut areas,
+        );
+
+        areas
+    }
+
+    pub fn area(&self, id: usize, width: usize, height: usize) -> Option<Rect> {
+        self.areas(width, height)
+            .into_iter()
+            .find(|(pane, _)| *pane == id)
+            .map(|(_, area)| area)
+    }
+
+    /// The pane bordering the focused one in the specified direction,
+    /// preferring the one alongside the top-left corner of the focused pane.
+    pub fn neighbour(&self, direction: Direction, width: usize, height: usize) -> Option<usize> {
+        let areas = self.areas(width, height);
+        let (_, focused) = areas.iter().find(|(pane, _)| *pane == self.focused)?;
+
+        areas
+            .iter()
+            .filter(|(_, area)| {
+                let overlaps = |(start, end): (usize, usize), (other_start, other_end)| {
+                    start < other_end && other_start < end
+                };
+
+                match direction {
+                    Direction::Left => {
+                        area.offsets().1 == focused.origin.offset
+                            && overlaps(area.lines(), focused.lines())
+                    }
+                    Direction::Right => {
+                        area.origin.offset == focused.offsets().1
+                            && overlaps(area.lines(), focused.lines())
+                    }
+                    Direction::Up => {
+                        area.lines().1 == focused.origin.line
+                            && overlaps(area.offsets(), focused.offsets())
+                    }
+                    Direction::Down => {
+                        area.origin.line == focused.lines().1
+                            && overlaps(area.offsets(), focused.offsets())
+                    }
+                }
+            })
+            .min_by_key(|(_, area)| match direction {
+                Direction::Left | Direction::Right => {
+                    area.origin.line.abs_diff(focused.origin.line)
+                }
+                Direction::Up | Direction::Down => {
+                    area.origin.offset.abs_diff(focused.origin.offset)
+                }
+            })
+            .map(|(pane, _)| *pane)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::new()
+    }
+}
+
+fn find(node: &mut Node, id: usize) -> Option<&mut Node> {
+    match node {
+        Node::Pane(pane) if *pane == id => Some(node),
+        Node::Pane(_) => None,
+        Node::Split { first, second, .. } => find(first, id).or_else(|| find(second, id)),
+    }
+}
+
+fn contains(node: &Node, id: usize) -> bool {
+    match node {
+        Node::Pane(pane) => *pane == id,
+        Node::Split { first, second, .. } => contains(first, id) || contains(second, id),
+    }
+}
+
+fn first_pane(node: &Node) -> usize {
+    match node {
+        Node::Pane(pane) => *pane,
+        Node::Split { first, .. } => first_pane(first),
+    }
+}
+
+// Replaces the split holding the pane with its other half,
+// returning the pane that should be focused in its place.
+fn remove(node: &mut Node, id: usize) -> Option<usize> {
+    let Node::Split { first, second, .. } = node else {
+        return None;
+    };
+
+    let remaining = match (&**first, &**second) {
+        (Node::Pane(pane), _) if *pane == id => second,
+        (_, Node::Pane(pane)) if *pane == id => first,
+        _ => return remove(first, id).or_else(|| remove(second, id)),
+    };
+    *node = mem::replace(&mut **remaining, Node::Pane(id));
+
+    Some(first_pane(node))
+}
+
+// Adjusts the innermost split holding the pane.
+fn resize(node: &mut Node, id: usize, amount: isize) -> bool {
+    let Node::Split {
+        ratio,
+        first,
+        second,
+        ..
+    } = node
+    else {
+        return false;
+    };
+
+    if resize(first, id, amount) || resize(second, id, amount) {
+        return true;
+    }
+
+    let change = if contains(first, id) {
+        amount
+    } else if contains(second, id) {
+        -amount
+    } else {
+        return false;
+    };
+    *ratio = (*ratio as isize + change).clamp(MIN_RATIO, MAX_RATIO) as usize;
+
+    true
+}
+
+fn collect_areas(node: &Node, area: Rect, areas: &mut Vec<(usize, Rect)>) {
+    match node {
+        Node::Pane(pane) => areas.push((*pane, area)),
+        Node::Split {
+            arrangement,
+            ratio,
+            first,
+            second,
+        } => {
+            let (first_area, second_area) = area.split(*arrangement, *ratio);
+            collect_areas(first, first_area, areas);
+            collect_areas(second, second_area, areas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arrangement, Direction, Layout, Pane, Rect};
+    use scribe::buffer::Position;
+
+    fn rect(line: usize, offset: usize, width: usize, height: usize) -> Rect {
+        Rect {
+            origin: Position { line, offset },
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn split_divides_the_focused_pane_and_focuses_the_new_half() {
+        let mut layout = Layout::new();
+        let right = layout.split(Arrangement::SideBySide);
+        let bottom = layout.split(Arrangement::Stacked);
+
+        assert_eq!(layout.focused(), bottom);
+        assert_eq!(
+            layout.areas(80, 20),
+            vec![
+                (0, rect(0, 0, 40, 20)),
+                (right, rect(0, 40, 40, 10)),
+                (bottom, rect(10, 40, 40, 10)),
+            ]
+        );
+    }
+
+    #[test]
+    fn close_hands_the_pane_space_to_its_neighbour() {
+        let mut layout = Layout::new();
+        let right = layout.split(Arrangement::SideBySide);
+        layout.split(Arrangement::Stacked);
+
+        assert!(layout.close());
+        assert_eq!(layout.focused(), right);
+        assert_eq!(
+            layout.areas(80, 20),
+            vec![(0, rect(0, 0, 40, 20)), (right, rect(0, 40, 40, 20))]
+        );
+
+        layout.close();
+        assert!(!layout.close());
+        assert_eq!(layout.areas(80, 20), vec![(0, rect(0, 0, 80, 20))]);
+    }
+
+    #[test]
+    fn resize_grows_the_focused_pane_within_limits() {
+        let mut layout = Layout::new();
+        let right = layout.split(Arrangement::SideBySide);
+
+        layout.resize(10);
+        assert_eq!(layout.area(right, 80, 20), Some(rect(0, 32, 48, 20)));
+
+        layout.resize(100);
+        assert_eq!(layout.area(right, 80, 20), Some(rect(0, 8, 72, 20)));
+    }
+
+    #[test]
+    fn neighbour_finds_adjacent_panes() {
+        let mut layout = Layout::new();
+        let right = layout.split(Arrangement::SideBySide);
+        let bottom = layout.split(Arrangement::Stacked);
+
+        assert_eq!(layout.neighbour(Direction::Up, 80, 20), Some(right));
+        assert_eq!(layout.neighbour(Direction::Left, 80, 20), Some(0));
+        assert_eq!(layout.neighbour(Direction::Right, 80, 20), None);
+
+        layout.focus(0);
+        assert_eq!(layout.neighbour(Direction::Right, 80, 20), Some(right));
+        layout.focus(bottom);
+        assert_eq!(layout.neighbour(Direction::Down, 80, 20), None);
+    }
+
+    #[test]
+    fn shift_cursors_follows_lines_changed_in_another_pane() {
+        let mut layout = Layout::new();
+        let pane = Pane {
+            buffer_id: Some(1),
+            cursor: Position {
+                line: 10,
+                offset: 2,
+            },
+            line_count: 20,
+        };
+        *layout.pane_mut(0).unwrap() = pane;
+        layout.split(Arrangement::SideBySide);
+        layout.split(Arrangement::Stacked);
+        layout.pane_mut(2).unwrap().cursor = Position { line: 3, offset: 0 };
+
+        layout.shift_cursors(1, Some(5), 23);
+
+        assert_eq!(
+            layout.pane(0).unwrap().cursor,
+            Position {
+                line: 13,
+                offset: 2
+            }
+        );
+        assert_eq!(layout.pane(0).unwrap().line_count, 23);
+        assert_eq!(
+            layout.pane(2).unwrap().cursor,
+            Position { line: 3, offset: 0 }
+        );
+
+        // Removing the line a cursor was on leaves it at the change.
+        layout.shift_cursors(1, Some(11), 13);
+
+        assert_eq!(
+            layout.pane(0).unwrap().cursor,
+            Position {
+                line: 11,
+                offset: 2
+            }
+        );
+    }
+}
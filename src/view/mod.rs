@@ -2,6 +2,7 @@ mod buffer;
 pub mod color;
 mod data;
 mod event_listener;
+mod layout;
 mod presenter;
 mod style;
 pub mod terminal;
@@ -10,19 +11,22 @@ mod theme_loader;
 pub use self::buffer::{LexemeMapper, MappedLexeme};
 pub use self::color::{Colors, RGBColor};
 pub use self::data::StatusLineData;
+pub use self::layout::{Arrangement, Direction, Pane, Rect};
 pub use self::presenter::Presenter;
 pub use self::style::Style;
 pub use self::terminal::*;
 
-use self::buffer::ScrollableRegion;
+use self::buffer::{Folds, ScrollableRegion};
 use self::buffer::{RenderCache, RenderState};
 use self::event_listener::EventListener;
+use self::layout::Layout;
 use self::theme_loader::ThemeLoader;
 use crate::errors::*;
 use crate::input::Key;
 use crate::models::application::{Event, Preferences};
-use scribe::buffer::Buffer;
-use std::cell::RefCell;
+use crate::util::fold;
+use scribe::buffer::{Buffer, LineRange};
+use std::cell::{Cell, RefCell};
 use std::cmp;
 use std::collections::HashMap;
 use std::ops::Drop;
@@ -35,11 +39,16 @@ const RENDER_CACHE_FREQUENCY: usize = 100;
 
 pub struct View {
     terminal: Arc<Box<dyn Terminal + Sync + Send + 'static>>,
-    scrollable_regions: HashMap<usize, ScrollableRegion>,
+    layout: Layout,
+    scrollable_regions: HashMap<(usize, usize), ScrollableRegion>,
     render_caches: HashMap<usize, Rc<RefCell<HashMap<usize, RenderState>>>>,
+    folds: HashMap<usize, Rc<RefCell<Folds>>>,
+    revisions: HashMap<usize, Rc<Cell<usize>>>,
+    changed_lines: HashMap<usize, Rc<Cell<Option<usize>>>>,
     pub theme_set: ThemeSet,
     preferences: Rc<RefCell<Preferences>>,
     pub last_key: Option<Key>,
+    pub key_hint: Option<String>,
     event_channel: Sender<Event>,
     event_listener_killswitch: SyncSender<()>,
 }
@@ -59,27 +68,161 @@ impl View {
         Ok(View {
             terminal,
             last_key: None,
+            key_hint: None,
             preferences,
+            layout: Layout::new(),
             scrollable_regions: HashMap::new(),
             render_caches: HashMap::new(),
+            folds: HashMap::new(),
+            revisions: HashMap::new(),
+            changed_lines: HashMap::new(),
             theme_set,
             event_channel,
             event_listener_killswitch: killswitch_tx,
         })
     }
 
+    pub fn terminal(&self) -> Arc<Box<dyn Terminal + Sync + Send + 'static>> {
+        self.terminal.clone()
+    }
+
     pub fn build_presenter(&mut self) -> Result<Presenter<'_>> {
-        Presenter::new(self)
+        let pane = self.layout.focused();
+
+        Presenter::new(self, pane)
+    }
+
+    /// Builds a presenter confined to an unfocused pane.
+    pub fn build_pane_presenter(&mut self, pane: usize) -> Result<Presenter<'_>> {
+        Presenter::new(self, pane)
+    }
+
+    /// The screen area occupied by a pane.
+    pub fn pane_area(&self, pane: usize) -> Result<Rect> {
+        Ok(self
+            .layout
+            .area(pane, self.terminal.width(), self.terminal.height())
+            .ok_or("Pane doesn't exist")?)
+    }
+
+    pub fn focused_pane(&self) -> Pane {
+        self.layout
+            .pane(self.layout.focused())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Panes other than the focused one.
+    pub fn unfocused_panes(&self) -> Vec<(usize, Pane)> {
+        let focused = self.layout.focused();
+        let mut panes: Vec<(usize, Pane)> = self
+            .layout
+            .panes()
+            .filter(|(id, _)| **id != focused)
+            .map(|(id, pane)| (*id, *pane))
+            .collect();
+        panes.sort_by_key(|(id, _)| *id);
+
+        panes
+    }
+
+    /// Records the buffer shown in the focused pane, and where its cursor is,
+    /// moving the cursors of other panes showing it past any changed lines.
+    pub fn update_focused_pane(&mut self, buffer: Option<&Buffer>) {
+        let focused = self.layout.focused();
+        let buffer_id = buffer.and_then(|buffer| buffer.id);
+        let changed_line = buffer_id
+            .and_then(|id| self.changed_lines.get(&id))
+            .and_then(|line| line.take());
+
+        // Counting lines isn't free, so it's only done for shared buffers.
+        let shared = self
+            .layout
+            .panes()
+            .filter(|(_, pane)| buffer_id.is_some() && pane.buffer_id == buffer_id)
+            .count()
+            > 1;
+        let line_count = match (buffer, buffer_id) {
+            (Some(buffer), Some(id)) if shared => {
+                let line_count = buffer.line_count();
+                self.layout.shift_cursors(id, changed_line, line_count);
+
+                line_count
+            }
+            _ => 0,
+        };
+
+        if let Some(pane) = self.layout.pane_mut(focused) {
+            pane.buffer_id = buffer_id;
+            pane.cursor = buffer.map(|buffer| *buffer.cursor).unwrap_or_default();
+            pane.line_count = line_count;
+        }
+    }
+
+    /// Splits the focused pane in two, focusing the new half,
+    /// which starts out scrolled to the same spot.
+    pub fn split_pane(&mut self, arrangement: Arrangement) {
+        let focused = self.layout.focused();
+        let pane = self.layout.split(arrangement);
+
+        let offsets: Vec<(usize, usize)> = self
+            .scrollable_regions
+            .iter()
+            .filter(|((region_pane, _), _)| *region_pane == focused)
+            .map(|((_, buffer), region)| (*buffer, region.line_offset()))
+            .collect();
+        for (buffer, line_offset) in offsets {
+            let mut region = ScrollableRegion::new(self.terminal.width(), self.terminal.height());
+            region.scroll_down(line_offset);
+            self.scrollable_regions.insert((pane, buffer), region);
+        }
+    }
+
+    pub fn close_pane(&mut self) -> Result<()> {
+        let pane = self.layout.focused();
+        if !self.layout.close() {
+            bail!("Can't close the only pane");
+        }
+        self.scrollable_regions
+            .retain(|(region_pane, _), _| *region_pane != pane);
+
+        Ok(())
+    }
+
+    /// Grows the focused pane by a percentage of its split, or shrinks it given a negative amount.
+    pub fn resize_pane(&mut self, amount: isize) -> Result<()> {
+        if !self.layout.resize(amount) {
+            bail!("The only pane can't be resized");
+        }
+
+        Ok(())
+    }
+
+    pub fn neighbouring_pane(&self, direction: Direction) -> Option<usize> {
+        self.layout
+            .neighbour(direction, self.terminal.width(), self.terminal.height())
+    }
+
+    pub fn focus_pane(&mut self, pane: usize) -> Result<()> {
+        if !self.layout.focus(pane) {
+            bail!("Pane doesn't exist");
+        }
+
+        Ok(())
     }
 
+    /// Scrolls the cursor into view, unfolding its line if it's hidden.
     pub fn scroll_to_cursor(&mut self, buffer: &Buffer) -> Result<()> {
-        self.get_region(buffer)?.scroll_into_view(buffer);
+        let folds = self.reveal_cursor(buffer)?;
+        self.get_region(buffer)?.scroll_into_view(buffer, &folds);
 
         Ok(())
     }
 
+    /// Scrolls the cursor to the center of the view, unfolding its line if it's hidden.
     pub fn scroll_to_center(&mut self, buffer: &Buffer) -> Result<()> {
-        self.get_region(buffer)?.scroll_to_center(buffer);
+        let folds = self.reveal_cursor(buffer)?;
+        self.get_region(buffer)?.scroll_to_center(buffer, &folds);
 
         Ok(())
     }
@@ -93,7 +236,7 @@ impl View {
     pub fn scroll_down(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
         let current_offset = self.get_region(buffer)?.line_offset();
         let line_count = buffer.line_count();
-        let half_screen_height = self.terminal.height() / 2;
+        let half_screen_height = self.pane_area(self.layout.focused())?.height / 2;
 
         let max = if line_count > half_screen_height {
             let visible_line_count = line_count.saturating_sub(current_offset);
@@ -108,18 +251,107 @@ impl View {
         Ok(())
     }
 
+    /// A count of the changes made to the buffer since it was initialized,
+    /// which moves forward when they're undone or redone, too.
+    pub fn revision(&self, buffer: &Buffer) -> usize {
+        buffer
+            .id
+            .and_then(|id| self.revisions.get(&id))
+            .map_or(0, |revision| revision.get())
+    }
+
+    /// The first line shown for the buffer in the focused pane.
+    pub fn scroll_offset(&mut self, buffer: &Buffer) -> Result<usize> {
+        Ok(self.get_region(buffer)?.line_offset())
+    }
+
+    pub fn set_scroll_offset(&mut self, buffer: &Buffer, line_offset: usize) -> Result<()> {
+        let region = self.get_region(buffer)?;
+        region.scroll_up(region.line_offset());
+        region.scroll_down(line_offset);
+
+        Ok(())
+    }
+
     pub fn forget_buffer(&mut self, buffer: &Buffer) -> Result<()> {
-        self.scrollable_regions.remove(&buffer_key(buffer)?);
+        let key = buffer_key(buffer)?;
+        self.scrollable_regions
+            .retain(|(_, region_buffer), _| *region_buffer != key);
         self.render_caches.remove(&buffer_key(buffer)?);
+        self.folds.remove(&buffer_key(buffer)?);
+        self.revisions.remove(&buffer_key(buffer)?);
+        self.changed_lines.remove(&buffer_key(buffer)?);
 
         Ok(())
     }
 
-    fn get_region(&mut self, buffer: &Buffer) -> Result<&mut ScrollableRegion> {
+    pub fn fold(&mut self, buffer: &Buffer, range: LineRange) -> Result<()> {
+        self.get_folds(buffer)?
+            .borrow_mut()
+            .add(range, buffer.line_count());
+
+        Ok(())
+    }
+
+    /// Removes the fold including the specified line,
+    /// returning whether there was one to remove.
+    pub fn unfold(&mut self, buffer: &Buffer, line: usize) -> Result<bool> {
         Ok(self
-            .scrollable_regions
+            .get_folds(buffer)?
+            .borrow_mut()
+            .remove(line, buffer.line_count()))
+    }
+
+    pub fn unfold_all(&mut self, buffer: &Buffer) -> Result<()> {
+        self.get_folds(buffer)?.borrow_mut().clear();
+
+        Ok(())
+    }
+
+    pub fn folds(&mut self, buffer: &Buffer) -> Result<Vec<LineRange>> {
+        Ok(self
+            .get_folds(buffer)?
+            .borrow_mut()
+            .ranges(buffer.line_count())
+            .iter()
+            .map(|fold| LineRange::new(fold.start(), fold.end()))
+            .collect())
+    }
+
+    // Unfolds any fold hiding the cursor, returning those that remain.
+    fn reveal_cursor(&mut self, buffer: &Buffer) -> Result<Vec<LineRange>> {
+        let line = buffer.cursor.line;
+        let folds = self.folds(buffer)?;
+        if fold::hidden(&folds, line) {
+            self.unfold(buffer, line)?;
+
+            return self.folds(buffer);
+        }
+
+        Ok(folds)
+    }
+
+    fn get_folds(&mut self, buffer: &Buffer) -> Result<&Rc<RefCell<Folds>>> {
+        Ok(self
+            .folds
             .entry(buffer_key(buffer)?)
-            .or_insert(ScrollableRegion::new(self.terminal.clone())))
+            .or_insert_with(|| Rc::new(RefCell::new(Folds::new()))))
+    }
+
+    fn get_region(&mut self, buffer: &Buffer) -> Result<&mut ScrollableRegion> {
+        self.get_pane_region(self.layout.focused(), buffer)
+    }
+
+    // Each pane scrolls through its buffers independently.
+    fn get_pane_region(&mut self, pane: usize, buffer: &Buffer) -> Result<&mut ScrollableRegion> {
+        let area = self.pane_area(pane)?;
+        let region = self
+            .scrollable_regions
+            .entry((pane, buffer_key(buffer)?))
+            .or_insert(ScrollableRegion::new(area.width, area.height));
+        region.resize(area.width, area.height);
+
+        Ok(region)
     }
 
     fn get_render_cache(
@@ -154,11 +386,24 @@ impl View {
         let render_cache = Rc::new(RefCell::new(HashMap::new()));
         self.render_caches
             .insert(buffer_key(buffer)?, render_cache.clone());
+        let folds = Rc::new(RefCell::new(Folds::new()));
+        self.folds.insert(buffer_key(buffer)?, folds.clone());
+        let revision = Rc::new(Cell::new(0));
+        self.revisions.insert(buffer_key(buffer)?, revision.clone());
+        let changed_line = Rc::new(Cell::new(None));
+        self.changed_lines
+            .insert(buffer_key(buffer)?, changed_line.clone());
 
         buffer.change_callback = Some(Box::new(move |change_position| {
             render_cache
                 .borrow_mut()
                 .invalidate_from(change_position.line);
+            folds.borrow_mut().invalidate_from(change_position.line);
+            revision.set(revision.get() + 1);
+            let line = changed_line.get().map_or(change_position.line, |line: usize| {
+                cmp::min(line, change_position.line)
+            });
+            changed_line.set(Some(line));
         }));
 
         Ok(())
@@ -3,23 +3,29 @@ use crate::view::buffer::{BufferRenderer, LexemeMapper};
 use crate::view::color::{ColorMap, Colors};
 use crate::view::style::Style;
 use crate::view::terminal::{Cell, CursorType, TerminalBuffer};
-use crate::view::StatusLineData;
 use crate::view::View;
+use crate::view::{Rect, StatusLineData};
 use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
 use std::borrow::Cow;
 use syntect::highlighting::Theme;
 use syntect::parsing::SyntaxSet;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub struct Presenter<'p> {
+    area: Rect,
     cursor_position: Option<Position>,
+    pane: usize,
+    secondary_cursors: Vec<Position>,
     terminal_buffer: TerminalBuffer<'p>,
     theme: Theme,
     pub view: &'p mut View,
 }
 
 impl<'p> Presenter<'p> {
-    pub fn new(view: &mut View) -> Result<Presenter> {
+    /// Builds a presenter for the specified pane. Positions
+    /// given to it are relative to the pane's top-left corner.
+    pub fn new(view: &mut View, pane: usize) -> Result<Presenter> {
         let theme = {
             let preferences = view.preferences.borrow();
             let theme_name = preferences.theme();
@@ -31,20 +37,25 @@ impl<'p> Presenter<'p> {
             theme.clone()
         };
 
+        let area = view.pane_area(pane)?;
+
         Ok(Presenter {
+            area,
             cursor_position: None,
-            terminal_buffer: TerminalBuffer::new(view.terminal.width(), view.terminal.height()),
+            pane,
+            secondary_cursors: Vec::new(),
+            terminal_buffer: TerminalBuffer::new(area.width, area.height),
             theme,
             view,
         })
     }
 
     pub fn width(&self) -> usize {
-        self.view.terminal.width()
+        self.area.width
     }
 
     pub fn height(&self) -> usize {
-        self.view.terminal.height()
+        self.area.height
     }
 
     pub fn clear(&mut self) {
@@ -55,20 +66,19 @@ impl<'p> Presenter<'p> {
         self.cursor_position = position;
     }
 
+    pub fn set_secondary_cursors(&mut self, positions: &[Position]) {
+        self.secondary_cursors = positions.to_vec();
+    }
+
     pub fn set_cursor_type(&mut self, cursor_type: CursorType) {
         self.view.terminal.set_cursor_type(cursor_type);
     }
 
     pub fn present(&mut self) -> Result<()> {
-        for (position, cell) in self.terminal_buffer.iter() {
-            self.view.terminal.print(
-                &position,
-                cell.style,
-                self.theme.map_colors(cell.colors),
-                &cell.content,
-            )?;
-        }
-        self.view.terminal.set_cursor(self.cursor_position);
+        self.draw()?;
+        self.view
+            .terminal
+            .set_cursor(self.cursor_position.map(|position| self.screen_position(position)));
         self.view.terminal.present();
 
         Ok(())
@@ -82,14 +92,19 @@ impl<'p> Presenter<'p> {
         highlights: Option<&[Range]>,
         lexeme_mapper: Option<&'p mut dyn LexemeMapper>,
     ) -> Result<()> {
-        let scroll_offset = self.view.get_region(buffer)?.line_offset();
+        self.view.terminal.set_cursor(None);
+        let scroll_offset = self.view.get_pane_region(self.pane, buffer)?.line_offset();
+        let folds = self.view.folds(buffer)?;
         let lines = LineIterator::new(buffer_data);
 
         self.cursor_position = BufferRenderer::new(
             buffer,
+            &folds,
             highlights,
+            &self.secondary_cursors,
             scroll_offset,
-            &**self.view.terminal,
+            self.area.width,
+            self.area.height,
             &self.theme,
             &self.view.preferences.borrow(),
             self.view.get_render_cache(buffer)?,
@@ -102,7 +117,7 @@ impl<'p> Presenter<'p> {
     }
 
     pub fn print_status_line(&mut self, entries: &[StatusLineData]) {
-        let line = self.view.terminal.height() - 1;
+        let line = self.height() - 1;
 
         entries
             .iter()
@@ -112,13 +127,13 @@ impl<'p> Presenter<'p> {
                     1 => format!(
                         "{:width$}",
                         element.content,
-                        width = self.view.terminal.width(),
+                        width = self.width(),
                     ),
 
                     2 if index == entries.len() - 1 => format!(
                         "{:width$}",
                         element.content,
-                        width = self.view.terminal.width().saturating_sub(offset),
+                        width = self.width().saturating_sub(offset),
                     ),
                     2 => element.content.clone(),
 
@@ -127,7 +142,7 @@ impl<'p> Presenter<'p> {
                         format!(
                             "{:width$}",
                             element.content,
-                            width = self.view.terminal.width().saturating_sub(space),
+                            width = self.width().saturating_sub(space),
                         )
                     }
                     _ => element.content.clone(),
@@ -144,6 +159,49 @@ impl<'p> Presenter<'p> {
 
                 updated_offset
             });
+
+        // Partially entered key sequences are shown just to
+        // the left of the focused pane's last status line entry.
+        if self.pane != self.view.layout.focused() {
+            return;
+        }
+        if let Some(hint) = self.view.key_hint.clone() {
+            let reserved = match entries.last() {
+                Some(entry) if entries.len() > 2 => entry.content.graphemes(true).count(),
+                _ => 0,
+            };
+            let width = hint.graphemes(true).count();
+            let offset = self.width().saturating_sub(reserved + width);
+
+            self.print(
+                &Position { line, offset },
+                Style::Default,
+                Colors::Focused,
+                hint,
+            );
+        }
+    }
+
+    /// Writes the pane's contents to the terminal without presenting them,
+    /// leaving that to the presenter for the focused pane.
+    pub fn draw(&mut self) -> Result<()> {
+        for (position, cell) in self.terminal_buffer.iter() {
+            self.view.terminal.print(
+                &self.screen_position(position),
+                cell.style,
+                self.theme.map_colors(cell.colors),
+                &cell.content,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn screen_position(&self, position: Position) -> Position {
+        Position {
+            line: self.area.origin.line + position.line,
+            offset: self.area.origin.offset + position.offset,
+        }
     }
 
     pub fn print<C>(&mut self, position: &Position, style: Style, colors: Colors, content: C)
@@ -34,6 +34,7 @@ pub trait Terminal {
     fn set_cursor(&self, _: Option<Position>);
     fn set_cursor_type(&self, _: CursorType);
     fn print(&self, _: &Position, _: Style, _: Colors, _: &str) -> Result<()>;
+    fn set_clipboard(&self, _: &str) -> Result<()>;
     fn suspend(&self);
 }
 
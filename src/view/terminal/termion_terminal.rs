@@ -2,7 +2,7 @@ extern crate libc;
 extern crate termion;
 
 use self::termion::color::{Bg, Fg};
-use self::termion::input::{Keys, TermRead};
+use self::termion::input::{Events as InputEvents, TermRead};
 use self::termion::raw::{IntoRawMode, RawTerminal};
 use self::termion::screen::{AlternateScreen, IntoAlternateScreen};
 use self::termion::style;
@@ -10,6 +10,8 @@ use self::termion::{color, cursor};
 use super::Terminal;
 use crate::errors::*;
 use crate::view::{Colors, CursorType, Style};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use mio::unix::EventedFd;
 use mio::{Events, Poll, PollOpt, Ready, Token};
 use scribe::buffer::{Distance, Position};
@@ -24,7 +26,7 @@ use std::sync::Mutex;
 use std::time::Duration;
 use unicode_segmentation::UnicodeSegmentation;
 
-use self::termion::event::Key as TermionKey;
+use self::termion::event::{Event as TermionEvent, Key as TermionKey};
 use crate::input::Key;
 use crate::models::application::Event;
 
@@ -34,7 +36,7 @@ const RESIZE: Token = Token(1);
 pub struct TermionTerminal {
     event_listener: Poll,
     signals: Signals,
-    input: Mutex<Option<Keys<Stdin>>>,
+    input: Mutex<Option<InputEvents<Stdin>>>,
     output: Mutex<Option<BufWriter<RawTerminal<AlternateScreen<Stdout>>>>>,
     current_style: Mutex<Option<Style>>,
     current_colors: Mutex<Option<Colors>>,
@@ -49,7 +51,7 @@ impl TermionTerminal {
         Ok(TermionTerminal {
             event_listener,
             signals,
-            input: Mutex::new(Some(stdin().keys())),
+            input: Mutex::new(Some(stdin().events())),
             output: Mutex::new(Some(create_output_instance())),
             current_style: Mutex::new(None),
             current_colors: Mutex::new(None),
@@ -146,27 +148,9 @@ impl Terminal for TermionTerminal {
                     let mut guard = self.input.lock().ok()?;
                     let input_handle = guard.as_mut()?;
                     let input_data = input_handle.next()?;
-                    let key = input_data.ok()?;
-
-                    match key {
-                        TermionKey::Backspace => Some(Event::Key(Key::Backspace)),
-                        TermionKey::Left => Some(Event::Key(Key::Left)),
-                        TermionKey::Right => Some(Event::Key(Key::Right)),
-                        TermionKey::Up => Some(Event::Key(Key::Up)),
-                        TermionKey::Down => Some(Event::Key(Key::Down)),
-                        TermionKey::Home => Some(Event::Key(Key::Home)),
-                        TermionKey::End => Some(Event::Key(Key::End)),
-                        TermionKey::PageUp => Some(Event::Key(Key::PageUp)),
-                        TermionKey::PageDown => Some(Event::Key(Key::PageDown)),
-                        TermionKey::Delete => Some(Event::Key(Key::Delete)),
-                        TermionKey::Insert => Some(Event::Key(Key::Insert)),
-                        TermionKey::Esc => Some(Event::Key(Key::Esc)),
-                        TermionKey::Char('\n') => Some(Event::Key(Key::Enter)),
-                        TermionKey::Char('\t') => Some(Event::Key(Key::Tab)),
-                        TermionKey::Char(c) => Some(Event::Key(Key::Char(c))),
-                        TermionKey::Ctrl(c) => Some(Event::Key(Key::Ctrl(c))),
-                        _ => None,
-                    }
+                    let event = input_data.ok()?;
+
+                    translate_event(event).map(Event::Key)
                 }
                 RESIZE => {
                     self.signals.into_iter().next();
@@ -279,6 +263,15 @@ impl Terminal for TermionTerminal {
         Ok(())
     }
 
+    fn set_clipboard(&self, content: &str) -> Result<()> {
+        let mut guard = self.output.lock().map_err(|_| LOCK_POISONED)?;
+        let output = guard.borrow_mut().as_mut().ok_or(STDOUT_FAILED)?;
+
+        write!(output, "{}", osc52_sequence(content))
+            .and_then(|_| output.flush())
+            .chain_err(|| "Failed to write to the terminal clipboard")
+    }
+
     fn suspend(&self) {
         self.restore_cursor();
         self.set_cursor(Some(Position { line: 0, offset: 0 }));
@@ -303,11 +296,64 @@ impl Terminal for TermionTerminal {
             guard.replace(create_output_instance());
         }
         if let Ok(mut guard) = self.input.lock() {
-            guard.replace(stdin().keys());
+            guard.replace(stdin().events());
         }
     }
 }
 
+// Asks the terminal emulator to place the content on its clipboard.
+fn osc52_sequence(content: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(content))
+}
+
+fn translate_event(event: TermionEvent) -> Option<Key> {
+    match event {
+        TermionEvent::Key(key) => match key {
+            TermionKey::Backspace => Some(Key::Backspace),
+            TermionKey::Left => Some(Key::Left),
+            TermionKey::Right => Some(Key::Right),
+            TermionKey::Up => Some(Key::Up),
+            TermionKey::Down => Some(Key::Down),
+            TermionKey::Home => Some(Key::Home),
+            TermionKey::End => Some(Key::End),
+            TermionKey::PageUp => Some(Key::PageUp),
+            TermionKey::PageDown => Some(Key::PageDown),
+            TermionKey::BackTab => Some(Key::BackTab),
+            TermionKey::Delete => Some(Key::Delete),
+            TermionKey::Insert => Some(Key::Insert),
+            TermionKey::Esc => Some(Key::Esc),
+            TermionKey::F(n) => Some(Key::F(n)),
+            TermionKey::Char('\n') => Some(Key::Enter),
+            TermionKey::Char('\t') => Some(Key::Tab),
+            TermionKey::Char(c) => Some(Key::Char(c)),
+            TermionKey::Ctrl(c) => Some(Key::Ctrl(c)),
+            TermionKey::Alt(c) => Some(Key::Alt(c)),
+            _ => None,
+        },
+        TermionEvent::Unsupported(data) => translate_modified_arrow(&data),
+        _ => None,
+    }
+}
+
+// Termion doesn't recognize arrow keys with modifiers,
+// which terminals send as "ESC [ 1 ; <modifier> <direction>".
+fn translate_modified_arrow(data: &[u8]) -> Option<Key> {
+    match data {
+        [0x1B, b'[', b'1', b';', modifier, direction] => match (modifier, direction) {
+            (b'2', b'A') => Some(Key::ShiftUp),
+            (b'2', b'B') => Some(Key::ShiftDown),
+            (b'2', b'C') => Some(Key::ShiftRight),
+            (b'2', b'D') => Some(Key::ShiftLeft),
+            (b'5', b'A') => Some(Key::CtrlUp),
+            (b'5', b'B') => Some(Key::CtrlDown),
+            (b'5', b'C') => Some(Key::CtrlRight),
+            (b'5', b'D') => Some(Key::CtrlLeft),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 impl Drop for TermionTerminal {
     fn drop(&mut self) {
         self.restore_cursor();
@@ -358,3 +404,25 @@ fn map_style(style: Style) -> Option<Box<dyn Display>> {
         Style::Italic => Some(Box::new(style::Italic)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{osc52_sequence, translate_modified_arrow};
+    use crate::input::Key;
+
+    #[test]
+    fn osc52_sequence_base64_encodes_the_content() {
+        assert_eq!(osc52_sequence("nexedit"), "\x1b]52;c;bmV4ZWRpdA==\x07");
+    }
+
+    #[test]
+    fn translate_modified_arrow_recognizes_shift_and_ctrl() {
+        assert_eq!(translate_modified_arrow(b"\x1b[1;2A"), Some(Key::ShiftUp));
+        assert_eq!(translate_modified_arrow(b"\x1b[1;5D"), Some(Key::CtrlLeft));
+    }
+
+    #[test]
+    fn translate_modified_arrow_ignores_other_modifiers() {
+        assert_eq!(translate_modified_arrow(b"\x1b[1;3A"), None);
+    }
+}
@@ -81,6 +81,9 @@ impl Terminal for TestTerminal {
         *cursor = position;
     }
     fn set_cursor_type(&self, _: CursorType) {}
+    fn set_clipboard(&self, _: &str) -> Result<()> {
+        Ok(())
+    }
     fn suspend(&self) {}
     fn print(&self, position: &Position, _: Style, colors: Colors, content: &str) -> Result<()> {
         if position.line >= self.height() {